@@ -0,0 +1,135 @@
+use std::{collections::HashMap, fmt::Display};
+
+use prost_types::{SourceCodeInfo, source_code_info::Location};
+
+use crate::string_builder::StringBuilder;
+
+// Field numbers used in SourceCodeInfo paths.
+pub const FILE_MESSAGE_TYPE: i32 = 4;
+pub const FILE_ENUM_TYPE: i32 = 5;
+pub const FILE_SERVICE: i32 = 6;
+pub const MESSAGE_FIELD: i32 = 2;
+pub const MESSAGE_NESTED_TYPE: i32 = 3;
+pub const MESSAGE_ENUM_TYPE: i32 = 4;
+pub const MESSAGE_ONEOF_DECL: i32 = 8;
+pub const ENUM_VALUE: i32 = 2;
+pub const SERVICE_METHOD: i32 = 2;
+
+/// Comments from the .proto source, looked up by descriptor path.
+pub struct Comments {
+    locations: HashMap<Vec<i32>, Location>,
+}
+
+impl Comments {
+    pub fn new(source_code_info: Option<&SourceCodeInfo>) -> Self {
+        let mut locations = HashMap::new();
+
+        if let Some(source_code_info) = source_code_info {
+            for location in &source_code_info.location {
+                if location.leading_comments.is_some() || location.trailing_comments.is_some() {
+                    locations
+                        .entry(location.path.clone())
+                        .or_insert_with(|| location.clone());
+                }
+            }
+        }
+
+        Self { locations }
+    }
+
+    /// Pushes `declaration` along with the comments attached to `path`, if any. Leading comments
+    /// become a `--[=[ ]=]` block above it so luau-lsp shows them on hover, and trailing comments
+    /// stay as `--` at the end of the line, or below it if there's more than one line.
+    /// `deprecated` adds an `@deprecated` tag to the block, which luau-lsp warns about on use.
+    pub fn push<T: Display>(
+        &self,
+        builder: &mut StringBuilder,
+        path: &[i32],
+        deprecated: bool,
+        declaration: T,
+    ) {
+        let location = self.locations.get(path);
+
+        let leading = location.and_then(|location| location.leading_comments.as_deref());
+        if leading.is_some() || deprecated {
+            let mut lines = leading.map(comment_lines).unwrap_or_default();
+            if deprecated {
+                if !lines.is_empty() {
+                    lines.push("");
+                }
+
+                lines.push("@deprecated");
+            }
+
+            let equals = "=".repeat(block_level(&lines));
+
+            match lines.as_slice() {
+                [] => {}
+                [line] => builder.push(format!("--[{equals}[ {line} ]{equals}]")),
+                lines => {
+                    builder.push(format!("--[{equals}["));
+                    for line in lines {
+                        if line.is_empty() {
+                            builder.blank();
+                        } else {
+                            builder.push(format!("\t{line}"));
+                        }
+                    }
+                    builder.push(format!("]{equals}]"));
+                }
+            }
+        }
+
+        let trailing = location
+            .and_then(|location| location.trailing_comments.as_deref())
+            .map(comment_lines)
+            .unwrap_or_default();
+
+        match trailing.as_slice() {
+            [line] => builder.push(format!("{declaration} -- {line}")),
+            lines => {
+                builder.push(declaration);
+
+                for line in lines {
+                    if line.is_empty() {
+                        builder.push("--");
+                    } else {
+                        builder.push(format!("-- {line}"));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn comment_lines(comment: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = comment
+        .lines()
+        .map(|line| {
+            let line = line.trim_end();
+            line.strip_prefix(' ').unwrap_or(line)
+        })
+        .collect();
+
+    while lines.first().is_some_and(|line| line.is_empty()) {
+        lines.remove(0);
+    }
+
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+
+    lines
+}
+
+// The number of `=`s needed so that nothing in the comment closes the block early.
+fn block_level(lines: &[&str]) -> usize {
+    let mut level = 1;
+    while lines
+        .iter()
+        .any(|line| line.contains(&format!("]{}]", "=".repeat(level))))
+    {
+        level += 1;
+    }
+    level
+}
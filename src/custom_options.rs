@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+
+use prost::{bytes::Buf, encoding::decode_varint};
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+    field_descriptor_proto::{Label, Type},
+};
+
+use crate::fields::{luau_float_literal, luau_key, luau_string_literal};
+
+// Custom options are extensions of descriptor.proto's options messages, which protoc hands us still
+// serialized. They're decoded here with the extensions, messages, and enums from the request,
+// into Luau tables keyed by the extension's full name, such as `{ ["my.pkg.ui_label"] = "Name" }`.
+
+pub const MESSAGE_OPTIONS: &str = ".google.protobuf.MessageOptions";
+pub const FIELD_OPTIONS: &str = ".google.protobuf.FieldOptions";
+pub const ONEOF_OPTIONS: &str = ".google.protobuf.OneofOptions";
+pub const ENUM_OPTIONS: &str = ".google.protobuf.EnumOptions";
+pub const ENUM_VALUE_OPTIONS: &str = ".google.protobuf.EnumValueOptions";
+pub const SERVICE_OPTIONS: &str = ".google.protobuf.ServiceOptions";
+pub const METHOD_OPTIONS: &str = ".google.protobuf.MethodOptions";
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_I64: u64 = 1;
+const WIRE_LEN: u64 = 2;
+const WIRE_START_GROUP: u64 = 3;
+const WIRE_END_GROUP: u64 = 4;
+const WIRE_I32: u64 = 5;
+
+/// Every extension, message, and enum in the request, by full name with the leading dot.
+#[derive(Default)]
+pub struct CustomOptionTypes<'a> {
+    extensions: HashMap<(&'a str, i32), (String, &'a FieldDescriptorProto)>,
+    messages: HashMap<String, &'a DescriptorProto>,
+    enums: HashMap<String, &'a EnumDescriptorProto>,
+}
+
+enum OptionValue {
+    Scalar(String),
+    List(Vec<OptionValue>),
+    Message(Vec<(String, OptionValue)>),
+    Map(Vec<(String, OptionValue)>),
+}
+
+impl OptionValue {
+    // Options set bit by bit, such as `(a).b = 1, (a).c = 2`, can show up more than once.
+    fn merge(&mut self, other: OptionValue) {
+        match (self, other) {
+            (OptionValue::List(values), OptionValue::List(others)) => values.extend(others),
+            (OptionValue::Map(entries), OptionValue::Map(others)) => {
+                for (key, value) in others {
+                    entries.retain(|(existing, _)| *existing != key);
+                    entries.push((key, value));
+                }
+            }
+            (OptionValue::Message(fields), OptionValue::Message(others)) => {
+                merge_fields(fields, others);
+            }
+            (this, other) => *this = other,
+        }
+    }
+
+    fn to_luau(&self) -> String {
+        match self {
+            OptionValue::Scalar(literal) => literal.clone(),
+            OptionValue::List(values) => {
+                let values: Vec<_> = values.iter().map(OptionValue::to_luau).collect();
+                format!("{{ {} }}", values.join(", "))
+            }
+            OptionValue::Message(fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(key, value)| format!("{key} = {}", value.to_luau()))
+                    .collect();
+                format!("{{ {} }}", fields.join(", "))
+            }
+            OptionValue::Map(entries) => {
+                let entries: Vec<_> = entries
+                    .iter()
+                    .map(|(key, value)| format!("[{key}] = {}", value.to_luau()))
+                    .collect();
+                format!("{{ {} }}", entries.join(", "))
+            }
+        }
+    }
+}
+
+fn merge_fields(fields: &mut Vec<(String, OptionValue)>, others: Vec<(String, OptionValue)>) {
+    for (key, value) in others {
+        match fields.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, existing)) => existing.merge(value),
+            None => fields.push((key, value)),
+        }
+    }
+}
+
+impl<'a> CustomOptionTypes<'a> {
+    pub fn new(files: &'a [FileDescriptorProto]) -> Self {
+        let mut types = CustomOptionTypes::default();
+
+        for file in files {
+            let scope = if file.package().is_empty() {
+                String::new()
+            } else {
+                format!(".{}", file.package())
+            };
+
+            types.add_extensions(&file.extension, &scope);
+
+            for message in &file.message_type {
+                types.add_message(message, &scope);
+            }
+
+            for descriptor in &file.enum_type {
+                types
+                    .enums
+                    .insert(format!("{scope}.{}", descriptor.name()), descriptor);
+            }
+        }
+
+        types
+    }
+
+    fn add_message(&mut self, message: &'a DescriptorProto, scope: &str) {
+        let full_name = format!("{scope}.{}", message.name());
+
+        self.add_extensions(&message.extension, &full_name);
+
+        for nested_type in &message.nested_type {
+            self.add_message(nested_type, &full_name);
+        }
+
+        for descriptor in &message.enum_type {
+            self.enums
+                .insert(format!("{full_name}.{}", descriptor.name()), descriptor);
+        }
+
+        self.messages.insert(full_name, message);
+    }
+
+    fn add_extensions(&mut self, extensions: &'a [FieldDescriptorProto], scope: &str) {
+        for extension in extensions {
+            self.extensions.insert(
+                (extension.extendee(), extension.number()),
+                (
+                    format!("{scope}.{}", extension.name())
+                        .trim_start_matches('.')
+                        .to_owned(),
+                    extension,
+                ),
+            );
+        }
+    }
+
+    /// The custom options in serialized `options` of the given type, such as
+    /// `MESSAGE_OPTIONS`, as a Luau table. `None` when there aren't any.
+    pub fn luau_table(&self, options_type: &str, options: &[u8]) -> Option<String> {
+        let mut buffer = options;
+        // The fields of the options message itself are the standard options, which we already have.
+        let fields = self.decode_fields(&mut buffer, options_type, None, None)?;
+
+        if fields.is_empty() {
+            return None;
+        }
+
+        Some(OptionValue::Message(fields).to_luau())
+    }
+
+    // The key and descriptor of an extension of the message, or one of the message's own fields.
+    fn field(
+        &self,
+        message_type: &str,
+        message: Option<&'a DescriptorProto>,
+        number: i32,
+    ) -> Option<(String, &'a FieldDescriptorProto)> {
+        if let Some((name, extension)) = self.extensions.get(&(message_type, number)) {
+            return Some((format!("[\"{name}\"]"), extension));
+        }
+
+        let field = message?
+            .field
+            .iter()
+            .find(|field| field.number() == number)?;
+
+        Some((luau_key(field.name()), field))
+    }
+
+    // Reads fields until the end of the buffer, or the end of the group `group` if given.
+    // `None` if the input is malformed, which protoc never gives us.
+    fn decode_fields(
+        &self,
+        buffer: &mut &[u8],
+        message_type: &str,
+        message: Option<&'a DescriptorProto>,
+        group: Option<u32>,
+    ) -> Option<Vec<(String, OptionValue)>> {
+        let mut fields = Vec::new();
+
+        while buffer.has_remaining() {
+            let key = decode_varint(buffer).ok()?;
+            let number = u32::try_from(key >> 3).ok()?;
+            let wire_type = key & 7;
+
+            if wire_type == WIRE_END_GROUP {
+                return (group == Some(number)).then_some(fields);
+            }
+
+            let value = match self.field(message_type, message, number as i32) {
+                Some((key, field)) => self
+                    .decode_value(buffer, field, wire_type, number)?
+                    .map(|value| (key, value)),
+
+                None => {
+                    self.skip_value(buffer, wire_type, number)?;
+                    None
+                }
+            };
+
+            if let Some((key, value)) = value {
+                merge_fields(&mut fields, vec![(key, value)]);
+            }
+        }
+
+        group.is_none().then_some(fields)
+    }
+
+    fn decode_value(
+        &self,
+        buffer: &mut &[u8],
+        field: &FieldDescriptorProto,
+        wire_type: u64,
+        number: u32,
+    ) -> Option<Option<OptionValue>> {
+        let repeated = field.label() == Label::Repeated;
+
+        let value = match (field.r#type(), wire_type) {
+            (Type::Message, WIRE_LEN) => {
+                let mut contents = take_length_delimited(buffer)?;
+                self.decode_message(&mut contents, field.type_name(), None)?
+            }
+
+            (Type::Group, WIRE_START_GROUP) => {
+                self.decode_message(buffer, field.type_name(), Some(number))?
+            }
+
+            (Type::String, WIRE_LEN) => {
+                OptionValue::Scalar(luau_string_literal(take_length_delimited(buffer)?))
+            }
+
+            (Type::Bytes, WIRE_LEN) => OptionValue::Scalar(format!(
+                "buffer.fromstring({})",
+                luau_string_literal(take_length_delimited(buffer)?)
+            )),
+
+            // Packed
+            (_, WIRE_LEN) if repeated => {
+                let mut contents = take_length_delimited(buffer)?;
+                let element_wire_type = match field.r#type() {
+                    Type::Double | Type::Fixed64 | Type::Sfixed64 => WIRE_I64,
+                    Type::Float | Type::Fixed32 | Type::Sfixed32 => WIRE_I32,
+                    _ => WIRE_VARINT,
+                };
+
+                let mut values = Vec::new();
+                while contents.has_remaining() {
+                    values.push(self.decode_scalar(&mut contents, field, element_wire_type)?);
+                }
+
+                return Some(Some(OptionValue::List(values)));
+            }
+
+            _ => self.decode_scalar(buffer, field, wire_type)?,
+        };
+
+        if repeated {
+            if let OptionValue::Map(_) = value {
+                return Some(Some(value));
+            }
+
+            return Some(Some(OptionValue::List(vec![value])));
+        }
+
+        Some(Some(value))
+    }
+
+    fn decode_message(
+        &self,
+        buffer: &mut &[u8],
+        type_name: &str,
+        group: Option<u32>,
+    ) -> Option<OptionValue> {
+        let message = self.messages.get(type_name).copied();
+        let fields = self.decode_fields(buffer, type_name, message, group)?;
+
+        let Some(message) = message.filter(|message| {
+            message
+                .options
+                .as_ref()
+                .is_some_and(|options| options.map_entry())
+        }) else {
+            return Some(OptionValue::Message(fields));
+        };
+
+        // Map entries become a table of their own, to be merged with the rest of the map.
+        let mut key = None;
+        let mut value = None;
+        for (name, field_value) in fields {
+            match name.as_str() {
+                "key" => key = Some(field_value.to_luau()),
+                "value" => value = Some(field_value),
+                _ => {}
+            }
+        }
+
+        let default = |number| {
+            let field = message
+                .field
+                .iter()
+                .find(|field| field.number() == number)?;
+            Some(self.default_value(field))
+        };
+
+        let key = key.or_else(|| default(1).map(|key| key.to_luau()))?;
+        let value = value.or_else(|| default(2))?;
+        Some(OptionValue::Map(vec![(key, value)]))
+    }
+
+    // What a map entry's key or value is when it's left out.
+    fn default_value(&self, field: &FieldDescriptorProto) -> OptionValue {
+        OptionValue::Scalar(match field.r#type() {
+            Type::String => "\"\"".to_owned(),
+            Type::Bytes => "buffer.create(0)".to_owned(),
+            Type::Bool => "false".to_owned(),
+            Type::Float | Type::Double => "0.0".to_owned(),
+            Type::Message | Type::Group => return OptionValue::Message(Vec::new()),
+            Type::Enum => self.enum_value(field.type_name(), 0),
+            _ => "0".to_owned(),
+        })
+    }
+
+    fn decode_scalar(
+        &self,
+        buffer: &mut &[u8],
+        field: &FieldDescriptorProto,
+        wire_type: u64,
+    ) -> Option<OptionValue> {
+        let literal = match wire_type {
+            WIRE_VARINT => {
+                let value = decode_varint(buffer).ok()?;
+
+                match field.r#type() {
+                    Type::Int32 => (value as i32).to_string(),
+                    Type::Int64 => (value as i64).to_string(),
+                    Type::Uint32 => (value as u32).to_string(),
+                    Type::Uint64 => value.to_string(),
+                    Type::Sint32 | Type::Sint64 => {
+                        ((value >> 1) as i64 ^ -((value & 1) as i64)).to_string()
+                    }
+                    Type::Bool => (value != 0).to_string(),
+                    Type::Enum => self.enum_value(field.type_name(), value as i32),
+                    _ => return None,
+                }
+            }
+
+            WIRE_I32 => {
+                if buffer.remaining() < 4 {
+                    return None;
+                }
+
+                match field.r#type() {
+                    Type::Fixed32 => buffer.get_u32_le().to_string(),
+                    Type::Sfixed32 => buffer.get_i32_le().to_string(),
+                    Type::Float => luau_float_literal(f64::from(buffer.get_f32_le())),
+                    _ => return None,
+                }
+            }
+
+            WIRE_I64 => {
+                if buffer.remaining() < 8 {
+                    return None;
+                }
+
+                match field.r#type() {
+                    Type::Fixed64 => buffer.get_u64_le().to_string(),
+                    Type::Sfixed64 => buffer.get_i64_le().to_string(),
+                    Type::Double => luau_float_literal(buffer.get_f64_le()),
+                    _ => return None,
+                }
+            }
+
+            _ => return None,
+        };
+
+        Some(OptionValue::Scalar(literal))
+    }
+
+    // Enum values are their names, the same as in messages, or numbers if they're unknown.
+    fn enum_value(&self, type_name: &str, number: i32) -> String {
+        self.enums
+            .get(type_name)
+            .and_then(|descriptor| {
+                descriptor
+                    .value
+                    .iter()
+                    .find(|value| value.number() == number)
+            })
+            .map(|value| format!("\"{}\"", value.name()))
+            .unwrap_or_else(|| number.to_string())
+    }
+
+    fn skip_value(&self, buffer: &mut &[u8], wire_type: u64, number: u32) -> Option<()> {
+        match wire_type {
+            WIRE_VARINT => {
+                decode_varint(buffer).ok()?;
+            }
+
+            WIRE_I64 | WIRE_I32 => {
+                let size = if wire_type == WIRE_I64 { 8 } else { 4 };
+                if buffer.len() < size {
+                    return None;
+                }
+
+                buffer.advance(size);
+            }
+
+            WIRE_LEN => {
+                take_length_delimited(buffer)?;
+            }
+
+            WIRE_START_GROUP => {
+                // Without a type, every field is skipped to the end of the group.
+                self.decode_fields(buffer, "", None, Some(number))?;
+            }
+
+            _ => return None,
+        }
+
+        Some(())
+    }
+}
+
+fn take_length_delimited<'b>(buffer: &mut &'b [u8]) -> Option<&'b [u8]> {
+    let length = usize::try_from(decode_varint(buffer).ok()?).ok()?;
+    if buffer.len() < length {
+        return None;
+    }
+
+    let (contents, rest) = buffer.split_at(length);
+    *buffer = rest;
+    Some(contents)
+}
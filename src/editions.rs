@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+    compiler::code_generator_response::Feature,
+    field_descriptor_proto::{Label, Type},
+};
+
+// prost-types predates editions, so it drops `edition` and every `features` option while decoding.
+// We decode the request a second time with just those fields, and line them up with the
+// prost-types descriptors by index.
+
+pub const EDITION_PROTO2: i32 = 998;
+pub const EDITION_PROTO3: i32 = 999;
+pub const EDITION_2023: i32 = 1000;
+pub const EDITION_2024: i32 = 1001;
+
+pub const MINIMUM_EDITION: i32 = EDITION_2023;
+pub const MAXIMUM_EDITION: i32 = EDITION_2024;
+
+const FEATURE_SUPPORTS_EDITIONS: u64 = 2;
+
+pub const SUPPORTED_FEATURES: u64 = Feature::Proto3Optional as u64 | FEATURE_SUPPORTS_EDITIONS;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EditionsRequest {
+    #[prost(message, repeated, tag = "15")]
+    pub proto_file: Vec<EditionsFile>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EditionsFile {
+    #[prost(message, repeated, tag = "4")]
+    pub message_type: Vec<EditionsMessage>,
+    #[prost(message, repeated, tag = "5")]
+    pub enum_type: Vec<EditionsEnum>,
+    #[prost(message, repeated, tag = "7")]
+    pub extension: Vec<EditionsField>,
+    #[prost(message, optional, tag = "8")]
+    pub options: Option<FileOptions>,
+    #[prost(int32, optional, tag = "14")]
+    pub edition: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EditionsMessage {
+    #[prost(message, repeated, tag = "2")]
+    pub field: Vec<EditionsField>,
+    #[prost(message, repeated, tag = "3")]
+    pub nested_type: Vec<EditionsMessage>,
+    #[prost(message, repeated, tag = "4")]
+    pub enum_type: Vec<EditionsEnum>,
+    #[prost(message, repeated, tag = "6")]
+    pub extension: Vec<EditionsField>,
+    #[prost(message, optional, tag = "7")]
+    pub options: Option<MessageOptions>,
+    #[prost(message, repeated, tag = "8")]
+    pub oneof_decl: Vec<EditionsOneof>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EditionsField {
+    #[prost(message, optional, tag = "8")]
+    pub options: Option<FieldOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EditionsOneof {
+    #[prost(message, optional, tag = "2")]
+    pub options: Option<OneofOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EditionsEnum {
+    #[prost(message, optional, tag = "3")]
+    pub options: Option<EnumOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FileOptions {
+    #[prost(message, optional, tag = "50")]
+    pub features: Option<FeatureSet>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessageOptions {
+    #[prost(message, optional, tag = "12")]
+    pub features: Option<FeatureSet>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FieldOptions {
+    #[prost(message, optional, tag = "21")]
+    pub features: Option<FeatureSet>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OneofOptions {
+    #[prost(message, optional, tag = "1")]
+    pub features: Option<FeatureSet>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EnumOptions {
+    #[prost(message, optional, tag = "7")]
+    pub features: Option<FeatureSet>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FeatureSet {
+    #[prost(int32, optional, tag = "1")]
+    pub field_presence: Option<i32>,
+    #[prost(int32, optional, tag = "2")]
+    pub enum_type: Option<i32>,
+    #[prost(int32, optional, tag = "3")]
+    pub repeated_field_encoding: Option<i32>,
+    #[prost(int32, optional, tag = "4")]
+    pub utf8_validation: Option<i32>,
+    #[prost(int32, optional, tag = "5")]
+    pub message_encoding: Option<i32>,
+    #[prost(int32, optional, tag = "6")]
+    pub json_format: Option<i32>,
+}
+
+/// The fields of `CodeGeneratorResponse` that prost-types doesn't know about.
+/// Encode it after the response, and protoc will merge the two.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct EditionsResponse {
+    #[prost(int32, optional, tag = "3")]
+    pub minimum_edition: Option<i32>,
+    #[prost(int32, optional, tag = "4")]
+    pub maximum_edition: Option<i32>,
+}
+
+impl EditionsResponse {
+    pub fn supported() -> Self {
+        Self {
+            minimum_edition: Some(MINIMUM_EDITION),
+            maximum_edition: Some(MAXIMUM_EDITION),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldPresence {
+    Explicit,
+    Implicit,
+    LegacyRequired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumKind {
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepeatedFieldEncoding {
+    Packed,
+    Expanded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Utf8Validation {
+    Verify,
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageEncoding {
+    LengthPrefixed,
+    Delimited,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonFormat {
+    Allow,
+    LegacyBestEffort,
+}
+
+/// Fully resolved features of a file, message, field, or enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Features {
+    pub field_presence: FieldPresence,
+    pub enum_type: EnumKind,
+    pub repeated_field_encoding: RepeatedFieldEncoding,
+    pub utf8_validation: Utf8Validation,
+    pub message_encoding: MessageEncoding,
+    // Only changes what protoc accepts, there's nothing for us to generate differently.
+    pub json_format: JsonFormat,
+}
+
+impl Features {
+    pub fn for_edition(edition: i32) -> Self {
+        match edition {
+            EDITION_PROTO2 => Self {
+                field_presence: FieldPresence::Explicit,
+                enum_type: EnumKind::Closed,
+                repeated_field_encoding: RepeatedFieldEncoding::Expanded,
+                utf8_validation: Utf8Validation::None,
+                message_encoding: MessageEncoding::LengthPrefixed,
+                json_format: JsonFormat::LegacyBestEffort,
+            },
+
+            EDITION_PROTO3 => Self {
+                field_presence: FieldPresence::Implicit,
+                enum_type: EnumKind::Open,
+                repeated_field_encoding: RepeatedFieldEncoding::Packed,
+                utf8_validation: Utf8Validation::Verify,
+                message_encoding: MessageEncoding::LengthPrefixed,
+                json_format: JsonFormat::Allow,
+            },
+
+            // 2023 and onwards
+            _ => Self {
+                field_presence: FieldPresence::Explicit,
+                ..Self::for_edition(EDITION_PROTO3)
+            },
+        }
+    }
+
+    fn merge(mut self, feature_set: Option<&FeatureSet>) -> Self {
+        let Some(feature_set) = feature_set else {
+            return self;
+        };
+
+        match feature_set.field_presence {
+            Some(1) => self.field_presence = FieldPresence::Explicit,
+            Some(2) => self.field_presence = FieldPresence::Implicit,
+            Some(3) => self.field_presence = FieldPresence::LegacyRequired,
+            _ => {}
+        }
+
+        match feature_set.enum_type {
+            Some(1) => self.enum_type = EnumKind::Open,
+            Some(2) => self.enum_type = EnumKind::Closed,
+            _ => {}
+        }
+
+        match feature_set.repeated_field_encoding {
+            Some(1) => self.repeated_field_encoding = RepeatedFieldEncoding::Packed,
+            Some(2) => self.repeated_field_encoding = RepeatedFieldEncoding::Expanded,
+            _ => {}
+        }
+
+        match feature_set.utf8_validation {
+            Some(2) => self.utf8_validation = Utf8Validation::Verify,
+            Some(3) => self.utf8_validation = Utf8Validation::None,
+            _ => {}
+        }
+
+        match feature_set.message_encoding {
+            Some(1) => self.message_encoding = MessageEncoding::LengthPrefixed,
+            Some(2) => self.message_encoding = MessageEncoding::Delimited,
+            _ => {}
+        }
+
+        match feature_set.json_format {
+            Some(1) => self.json_format = JsonFormat::Allow,
+            Some(2) => self.json_format = JsonFormat::LegacyBestEffort,
+            _ => {}
+        }
+
+        self
+    }
+
+    /// The `FeatureSet` that resolves to these features, with every feature set.
+    pub fn to_feature_set(self) -> FeatureSet {
+        FeatureSet {
+            field_presence: Some(match self.field_presence {
+                FieldPresence::Explicit => 1,
+                FieldPresence::Implicit => 2,
+                FieldPresence::LegacyRequired => 3,
+            }),
+            enum_type: Some(match self.enum_type {
+                EnumKind::Open => 1,
+                EnumKind::Closed => 2,
+            }),
+            repeated_field_encoding: Some(match self.repeated_field_encoding {
+                RepeatedFieldEncoding::Packed => 1,
+                RepeatedFieldEncoding::Expanded => 2,
+            }),
+            utf8_validation: Some(match self.utf8_validation {
+                Utf8Validation::Verify => 2,
+                Utf8Validation::None => 3,
+            }),
+            message_encoding: Some(match self.message_encoding {
+                MessageEncoding::LengthPrefixed => 1,
+                MessageEncoding::Delimited => 2,
+            }),
+            json_format: Some(match self.json_format {
+                JsonFormat::Allow => 1,
+                JsonFormat::LegacyBestEffort => 2,
+            }),
+        }
+    }
+
+    // proto2 and proto3 spell some features with the older syntax instead.
+    fn merge_legacy_field(mut self, field: &FieldDescriptorProto) -> Self {
+        if field.label() == Label::Required {
+            self.field_presence = FieldPresence::LegacyRequired;
+        }
+
+        if field.proto3_optional() {
+            self.field_presence = FieldPresence::Explicit;
+        }
+
+        if let Some(packed) = field.options.as_ref().and_then(|options| options.packed) {
+            self.repeated_field_encoding = if packed {
+                RepeatedFieldEncoding::Packed
+            } else {
+                RepeatedFieldEncoding::Expanded
+            };
+        }
+
+        if field.r#type() == Type::Group {
+            self.message_encoding = MessageEncoding::Delimited;
+        }
+
+        self
+    }
+}
+
+pub fn edition_of_file(file: &FileDescriptorProto, editions_file: Option<&EditionsFile>) -> i32 {
+    match file.syntax() {
+        "" | "proto2" => EDITION_PROTO2,
+        "proto3" => EDITION_PROTO3,
+        _ => editions_file
+            .and_then(|editions_file| editions_file.edition)
+            .unwrap_or_default(),
+    }
+}
+
+pub fn edition_name(edition: i32) -> String {
+    match edition {
+        EDITION_PROTO2 => "proto2".to_owned(),
+        EDITION_PROTO3 => "proto3".to_owned(),
+        EDITION_2023 => "2023".to_owned(),
+        EDITION_2024 => "2024".to_owned(),
+        other => format!("{other} (unknown)"),
+    }
+}
+
+/// Resolved features of everything in the request, keyed the same way as the export map:
+/// "package.Message", "package.Message.field", "package.Enum".
+/// Files are keyed by their file name.
+#[derive(Debug, Default)]
+pub struct FeatureMap {
+    features: HashMap<String, Features>,
+    editions: HashMap<String, i32>,
+}
+
+impl FeatureMap {
+    pub fn new(files: &[FileDescriptorProto], editions_request: &EditionsRequest) -> Self {
+        let mut feature_map = FeatureMap::default();
+
+        for (index, file) in files.iter().enumerate() {
+            let editions_file = editions_request.proto_file.get(index);
+            let edition = edition_of_file(file, editions_file);
+
+            let file_features = Features::for_edition(edition).merge(
+                editions_file
+                    .and_then(|editions_file| editions_file.options.as_ref())
+                    .and_then(|options| options.features.as_ref()),
+            );
+
+            feature_map.editions.insert(file.name().to_owned(), edition);
+            feature_map
+                .features
+                .insert(file.name().to_owned(), file_features);
+
+            for (index, descriptor) in file.enum_type.iter().enumerate() {
+                feature_map.add_enum(
+                    descriptor,
+                    editions_file.and_then(|editions_file| editions_file.enum_type.get(index)),
+                    file.package(),
+                    file_features,
+                );
+            }
+
+            for (index, descriptor) in file.message_type.iter().enumerate() {
+                feature_map.add_message(
+                    descriptor,
+                    editions_file.and_then(|editions_file| editions_file.message_type.get(index)),
+                    file.package(),
+                    file_features,
+                );
+            }
+
+            for (index, descriptor) in file.extension.iter().enumerate() {
+                feature_map.add_field(
+                    descriptor,
+                    editions_file.and_then(|editions_file| editions_file.extension.get(index)),
+                    file.package(),
+                    file_features,
+                );
+            }
+        }
+
+        feature_map
+    }
+
+    fn add_message(
+        &mut self,
+        descriptor: &DescriptorProto,
+        editions_message: Option<&EditionsMessage>,
+        scope: &str,
+        parent_features: Features,
+    ) {
+        let full_name = format!("{scope}.{}", descriptor.name());
+
+        let message_features = parent_features.merge(
+            editions_message
+                .and_then(|editions_message| editions_message.options.as_ref())
+                .and_then(|options| options.features.as_ref()),
+        );
+
+        self.features.insert(full_name.clone(), message_features);
+
+        for (index, field) in descriptor.field.iter().enumerate() {
+            let oneof_features = match field.oneof_index {
+                Some(oneof_index) if !field.proto3_optional() => message_features.merge(
+                    editions_message
+                        .and_then(|editions_message| {
+                            editions_message.oneof_decl.get(oneof_index as usize)
+                        })
+                        .and_then(|oneof| oneof.options.as_ref())
+                        .and_then(|options| options.features.as_ref()),
+                ),
+
+                _ => message_features,
+            };
+
+            self.add_field(
+                field,
+                editions_message.and_then(|editions_message| editions_message.field.get(index)),
+                &full_name,
+                oneof_features,
+            );
+        }
+
+        for (index, field) in descriptor.extension.iter().enumerate() {
+            self.add_field(
+                field,
+                editions_message.and_then(|editions_message| editions_message.extension.get(index)),
+                &full_name,
+                message_features,
+            );
+        }
+
+        for (index, nested_type) in descriptor.nested_type.iter().enumerate() {
+            self.add_message(
+                nested_type,
+                editions_message
+                    .and_then(|editions_message| editions_message.nested_type.get(index)),
+                &full_name,
+                message_features,
+            );
+        }
+
+        for (index, nested_enum) in descriptor.enum_type.iter().enumerate() {
+            self.add_enum(
+                nested_enum,
+                editions_message.and_then(|editions_message| editions_message.enum_type.get(index)),
+                &full_name,
+                message_features,
+            );
+        }
+    }
+
+    fn add_field(
+        &mut self,
+        descriptor: &FieldDescriptorProto,
+        editions_field: Option<&EditionsField>,
+        scope: &str,
+        parent_features: Features,
+    ) {
+        let features = parent_features
+            .merge(
+                editions_field
+                    .and_then(|editions_field| editions_field.options.as_ref())
+                    .and_then(|options| options.features.as_ref()),
+            )
+            .merge_legacy_field(descriptor);
+
+        self.features
+            .insert(format!("{scope}.{}", descriptor.name()), features);
+    }
+
+    fn add_enum(
+        &mut self,
+        descriptor: &EnumDescriptorProto,
+        editions_enum: Option<&EditionsEnum>,
+        scope: &str,
+        parent_features: Features,
+    ) {
+        let features = parent_features.merge(
+            editions_enum
+                .and_then(|editions_enum| editions_enum.options.as_ref())
+                .and_then(|options| options.features.as_ref()),
+        );
+
+        self.features
+            .insert(format!("{scope}.{}", descriptor.name()), features);
+    }
+
+    pub fn edition(&self, file_name: &str) -> i32 {
+        self.editions.get(file_name).copied().unwrap_or_default()
+    }
+
+    /// Features of a message, enum, or file.
+    pub fn get(&self, name: &str) -> Features {
+        self.features
+            .get(name)
+            .copied()
+            .unwrap_or_else(|| panic!("no features resolved for {name}"))
+    }
+
+    pub fn field(&self, scope: &str, field: &FieldDescriptorProto) -> Features {
+        self.get(&format!("{scope}.{}", field.name()))
+    }
+}
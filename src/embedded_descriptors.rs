@@ -0,0 +1,184 @@
+use prost::{
+    Message,
+    encoding::{WireType, encode_key, encode_varint},
+};
+use prost_types::{DescriptorProto, EnumDescriptorProto, FileDescriptorProto};
+
+use crate::editions::{FeatureMap, MINIMUM_EDITION};
+
+// Field numbers in descriptor.proto that prost-types doesn't know about.
+const FILE_MESSAGE_TYPE: u32 = 4;
+const FILE_ENUM_TYPE: u32 = 5;
+const FILE_OPTIONS: u32 = 8;
+const FILE_EDITION: u32 = 14;
+const FILE_OPTIONS_FEATURES: u32 = 50;
+const MESSAGE_FIELD: u32 = 2;
+const MESSAGE_NESTED_TYPE: u32 = 3;
+const MESSAGE_ENUM_TYPE: u32 = 4;
+const MESSAGE_OPTIONS: u32 = 7;
+const MESSAGE_OPTIONS_FEATURES: u32 = 12;
+const FIELD_OPTIONS: u32 = 8;
+const FIELD_OPTIONS_FEATURES: u32 = 21;
+const ENUM_OPTIONS: u32 = 3;
+const ENUM_OPTIONS_FEATURES: u32 = 7;
+
+/// Serializes a file for `embed_descriptors`, without its source code info.
+///
+/// prost-types drops `edition` and `features`, so for editions files those are written back in by
+/// hand: every message, field, and enum gets its fully resolved features. Singular messages merge
+/// when they're repeated on the wire, so appending options only adds to the ones already there.
+pub fn encode_file(file: &FileDescriptorProto, feature_map: &FeatureMap) -> Vec<u8> {
+    let mut stripped = FileDescriptorProto {
+        source_code_info: None,
+        ..file.clone()
+    };
+
+    let edition = feature_map.edition(file.name());
+    if edition < MINIMUM_EDITION {
+        return stripped.encode_to_vec();
+    }
+
+    let message_types = std::mem::take(&mut stripped.message_type);
+    let enum_types = std::mem::take(&mut stripped.enum_type);
+
+    let mut output = stripped.encode_to_vec();
+
+    for message in &message_types {
+        let full_name = format!("{}.{}", file.package(), message.name());
+        encode_bytes(
+            FILE_MESSAGE_TYPE,
+            &encode_message(message, &full_name, feature_map),
+            &mut output,
+        );
+    }
+
+    for enum_type in &enum_types {
+        let full_name = format!("{}.{}", file.package(), enum_type.name());
+        encode_bytes(
+            FILE_ENUM_TYPE,
+            &encode_enum(enum_type, &full_name, feature_map),
+            &mut output,
+        );
+    }
+
+    encode_bytes(
+        FILE_OPTIONS,
+        &encode_features(FILE_OPTIONS_FEATURES, feature_map, file.name()),
+        &mut output,
+    );
+
+    encode_key(FILE_EDITION, WireType::Varint, &mut output);
+    encode_varint(edition as u64, &mut output);
+
+    output
+}
+
+fn encode_message(message: &DescriptorProto, full_name: &str, feature_map: &FeatureMap) -> Vec<u8> {
+    let mut stripped = message.clone();
+    let fields = std::mem::take(&mut stripped.field);
+    let nested_types = std::mem::take(&mut stripped.nested_type);
+    let enum_types = std::mem::take(&mut stripped.enum_type);
+
+    let mut output = stripped.encode_to_vec();
+
+    for field in &fields {
+        let mut encoded = field.encode_to_vec();
+        encode_bytes(
+            FIELD_OPTIONS,
+            &encode_features(
+                FIELD_OPTIONS_FEATURES,
+                feature_map,
+                &format!("{full_name}.{}", field.name()),
+            ),
+            &mut encoded,
+        );
+        encode_bytes(MESSAGE_FIELD, &encoded, &mut output);
+    }
+
+    for nested_type in &nested_types {
+        encode_bytes(
+            MESSAGE_NESTED_TYPE,
+            &encode_message(
+                nested_type,
+                &format!("{full_name}.{}", nested_type.name()),
+                feature_map,
+            ),
+            &mut output,
+        );
+    }
+
+    for enum_type in &enum_types {
+        encode_bytes(
+            MESSAGE_ENUM_TYPE,
+            &encode_enum(
+                enum_type,
+                &format!("{full_name}.{}", enum_type.name()),
+                feature_map,
+            ),
+            &mut output,
+        );
+    }
+
+    encode_bytes(
+        MESSAGE_OPTIONS,
+        &encode_features(MESSAGE_OPTIONS_FEATURES, feature_map, full_name),
+        &mut output,
+    );
+
+    output
+}
+
+fn encode_enum(
+    enum_type: &EnumDescriptorProto,
+    full_name: &str,
+    feature_map: &FeatureMap,
+) -> Vec<u8> {
+    let mut output = enum_type.encode_to_vec();
+    encode_bytes(
+        ENUM_OPTIONS,
+        &encode_features(ENUM_OPTIONS_FEATURES, feature_map, full_name),
+        &mut output,
+    );
+    output
+}
+
+// An options message with only its `features` set.
+fn encode_features(tag: u32, feature_map: &FeatureMap, name: &str) -> Vec<u8> {
+    let mut output = Vec::new();
+    encode_bytes(
+        tag,
+        &feature_map.get(name).to_feature_set().encode_to_vec(),
+        &mut output,
+    );
+    output
+}
+
+fn encode_bytes(tag: u32, bytes: &[u8], output: &mut Vec<u8>) {
+    encode_key(tag, WireType::LengthDelimited, output);
+    encode_varint(bytes.len() as u64, output);
+    output.extend_from_slice(bytes);
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard, padded base64, which `proto.json.deserializeBuffer` reads.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let triple = (chunk[0] as u32) << 16
+            | (chunk.get(1).copied().unwrap_or(0) as u32) << 8
+            | chunk.get(2).copied().unwrap_or(0) as u32;
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                output.push(BASE64_ALPHABET[(triple >> (18 - index * 6) & 0x3F) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
@@ -1,15 +1,18 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::borrow::Cow;
 
 use typed_path::UnixPath as Path;
 
 use prost_types::{
-    FieldDescriptorProto, FileDescriptorProto,
+    EnumOptions, EnumValueOptions, FieldDescriptorProto, FieldOptions, FileDescriptorProto,
+    MessageOptions, MethodOptions, ServiceOptions,
     field_descriptor_proto::{Label, Type},
 };
 
 use crate::{
-    generator::{ExportMap, MapType, file_path_export_name},
+    editions::{FeatureMap, Features, FieldPresence, RepeatedFieldEncoding, Utf8Validation},
+    generator::{EnumType, ExportMap, MapType, file_path_export_name},
     if_builder::IfBuilder,
+    options::OptionsMap,
     string_builder::StringBuilder,
 };
 
@@ -35,11 +38,54 @@ impl FieldNameCase {
     }
 }
 
+/// How 64-bit integers are represented in Luau.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Int64Type {
+    /// Plain numbers, which lose precision past 2^53 (default when no option is passed).
+    Number,
+    /// Decimal strings, such as `"-9223372036854775808"`.
+    String,
+    /// `proto.Int64`, a hi/lo pair with arithmetic, comparison, and `tostring`.
+    Int64,
+}
+
+impl Int64Type {
+    /// Map keys can't be `proto.Int64`, since tables would compare them by reference.
+    pub fn for_map_key(self) -> Self {
+        match self {
+            Int64Type::Int64 => Int64Type::String,
+            other => other,
+        }
+    }
+}
+
+/// The `proto.Int64Kind` of a 64-bit integer field, which says how it's laid out on the wire.
+fn int64_kind(field: &FieldDescriptorProto) -> Option<&'static str> {
+    match field.r#type() {
+        Type::Int64 => Some("int64"),
+        Type::Uint64 => Some("uint64"),
+        Type::Sint64 => Some("sint64"),
+        Type::Fixed64 => Some("fixed64"),
+        Type::Sfixed64 => Some("sfixed64"),
+        _ => None,
+    }
+}
+
+fn is_unsigned_64(field: &FieldDescriptorProto) -> bool {
+    matches!(field.r#type(), Type::Uint64 | Type::Fixed64)
+}
+
 pub struct FieldGenerator<'a> {
     pub field_kind: FieldKind<'a>,
     pub export_map: &'a ExportMap,
+    pub feature_map: &'a FeatureMap,
+    // Full name of the message the field is in
+    pub scope: &'a str,
     pub base_file: &'a FileDescriptorProto,
     pub field_name_case: FieldNameCase,
+    pub int64_type: Int64Type,
+    // Whether decoding collects what it doesn't know into `_unknownFields`
+    pub preserve_unknown_fields: bool,
 }
 
 #[derive(Debug)]
@@ -64,13 +110,55 @@ impl FieldGenerator<'_> {
                     return false;
                 }
 
-                field.proto3_optional() || matches!(field.r#type(), Type::Message)
+                matches!(field.r#type(), Type::Message | Type::Group)
+                    || self.features_of(field).field_presence != FieldPresence::Implicit
             }
 
             FieldKind::OneOf { .. } => true,
         }
     }
 
+    // proto2: `[default = ...]`
+    fn custom_default(&self) -> Option<String> {
+        match self.field_kind {
+            FieldKind::Single(field) => custom_default_of_field_descriptor(field, self.int64_type),
+            FieldKind::OneOf { .. } => None,
+        }
+    }
+
+    pub fn has_custom_default(&self) -> bool {
+        self.custom_default().is_some()
+    }
+
+    /// The value `new` gives the field. Fields with presence start out unset, even with a custom
+    /// default, so that the default isn't encoded. Their getter returns it instead.
+    pub fn initial(&self) -> Cow<'static, str> {
+        if self.has_presence() {
+            return "nil".into();
+        }
+
+        self.default()
+    }
+
+    pub fn is_required(&self) -> bool {
+        match &self.field_kind {
+            FieldKind::Single(field) => {
+                self.features_of(field).field_presence == FieldPresence::LegacyRequired
+            }
+
+            FieldKind::OneOf { .. } => false,
+        }
+    }
+
+    fn features_of(&self, field: &FieldDescriptorProto) -> Features {
+        self.feature_map.field(self.scope, field)
+    }
+
+    fn is_packed(&self, field: &FieldDescriptorProto) -> bool {
+        is_packable(field)
+            && self.features_of(field).repeated_field_encoding == RepeatedFieldEncoding::Packed
+    }
+
     pub fn name(&self) -> String {
         match &self.field_kind {
             FieldKind::Single(field) => self.luau_name(field.name()),
@@ -78,7 +166,7 @@ impl FieldGenerator<'_> {
         }
     }
 
-    fn luau_name(&self, raw: &str) -> String {
+    pub fn luau_name(&self, raw: &str) -> String {
         self.field_name_case.apply(raw)
     }
 
@@ -91,17 +179,23 @@ impl FieldGenerator<'_> {
                         type_definition_of_field_descriptor(
                             &map_type.key,
                             self.export_map,
-                            self.base_file
+                            self.base_file,
+                            self.int64_type.for_map_key(),
                         ),
                         type_definition_of_field_descriptor(
                             &map_type.value,
                             self.export_map,
-                            self.base_file
+                            self.base_file,
+                            self.int64_type,
                         ),
                     )
                 } else {
-                    let definition =
-                        type_definition_of_field_descriptor(field, self.export_map, self.base_file);
+                    let definition = type_definition_of_field_descriptor(
+                        field,
+                        self.export_map,
+                        self.base_file,
+                        self.int64_type,
+                    );
 
                     if field.label.is_some() && field.label() == Label::Repeated {
                         format!("{{ {definition} }}")
@@ -121,7 +215,8 @@ impl FieldGenerator<'_> {
                             type_definition_of_field_descriptor(
                                 field,
                                 self.export_map,
-                                self.base_file
+                                self.base_file,
+                                self.int64_type,
                             )
                         )
                     })
@@ -143,8 +238,11 @@ impl FieldGenerator<'_> {
     }
 
     pub fn should_encode(&self) -> String {
-        let this = format!("self.{}", self.name());
+        self.is_set(&format!("self.{}", self.name()))
+    }
 
+    /// A Luau condition for whether `this` holds anything other than the field's default.
+    fn is_set(&self, this: &str) -> String {
         if self.has_presence() {
             return format!("{this} ~= nil");
         }
@@ -163,22 +261,24 @@ impl FieldGenerator<'_> {
 
                 // TODO: Remove default branch and explicitly type everything out
                 match field.r#type() {
+                    Type::Int64 | Type::Uint64 | Type::Sint64 | Type::Sfixed64 | Type::Fixed64 => {
+                        match self.int64_type {
+                            Int64Type::Number => format!("{this} ~= nil and {this} ~= 0"),
+                            Int64Type::String => format!("{this} ~= nil and {this} ~= \"0\""),
+                            Int64Type::Int64 => format!("{this} ~= nil and not {this}:isZero()"),
+                        }
+                    }
                     Type::Int32
                     | Type::Uint32
-                    | Type::Int64
-                    | Type::Uint64
                     | Type::Sint32
-                    | Type::Sint64
                     | Type::Sfixed32
-                    | Type::Sfixed64
                     | Type::Fixed32
-                    | Type::Fixed64
                     | Type::Float
                     | Type::Double => {
                         format!("{this} ~= nil and {this} ~= 0")
                     }
                     Type::String => format!("{this} ~= nil and {this} ~= \"\""),
-                    Type::Bool => this,
+                    Type::Bool => this.to_owned(),
                     Type::Bytes => format!("{this} ~= nil and buffer.len({this}) > 0"),
                     Type::Enum => format!(
                         "{this} ~= nil and ({this} ~= nil and {this} ~= 0 or {this} ~= {}.fromNumber(0))",
@@ -188,9 +288,7 @@ impl FieldGenerator<'_> {
                             self.base_file
                         )
                     ),
-                    Type::Message => unreachable!("Message has presence"),
-
-                    Type::Group => unimplemented!("Group"),
+                    Type::Message | Type::Group => unreachable!("Message has presence"),
                 }
             }
         }
@@ -200,6 +298,14 @@ impl FieldGenerator<'_> {
         let this = format!("self.{}", self.name());
 
         let mut encode = StringBuilder::new();
+
+        if self.is_required() {
+            encode.push(format!(
+                "if {this} == nil then error(\"Missing required field {}\") end",
+                self.name()
+            ));
+        }
+
         encode.push(format!("if {} then", self.should_encode()));
 
         match &self.field_kind {
@@ -217,6 +323,7 @@ impl FieldGenerator<'_> {
                             &map_type.key,
                             self.export_map,
                             self.base_file,
+                            self.int64_type.for_map_key(),
                             "key",
                         )
                         .replace("cursor", "mapCursor")
@@ -228,6 +335,7 @@ impl FieldGenerator<'_> {
                             &map_type.value,
                             self.export_map,
                             self.base_file,
+                            self.int64_type,
                             "value",
                         )
                         .replace("cursor", "mapCursor")
@@ -241,12 +349,13 @@ impl FieldGenerator<'_> {
 
                     encode.push("end");
                 } else if field.label.is_some() && field.label() == Label::Repeated {
-                    if is_packed(field) {
+                    if self.is_packed(field) {
                         let field_number = field.number();
                         let write_value = encode_field_descriptor_ignore_repeated_instruction(
                             field,
                             self.export_map,
                             self.base_file,
+                            self.int64_type,
                             "value",
                         )
                         .replace("cursor", "packedCursor")
@@ -271,6 +380,7 @@ impl FieldGenerator<'_> {
                             field,
                             self.export_map,
                             self.base_file,
+                            self.int64_type,
                             "value",
                         ));
 
@@ -282,6 +392,7 @@ impl FieldGenerator<'_> {
                         field,
                         self.export_map,
                         self.base_file,
+                        self.int64_type,
                         &this,
                     ));
                 }
@@ -298,6 +409,7 @@ impl FieldGenerator<'_> {
                                 field,
                                 self.export_map,
                                 self.base_file,
+                                self.int64_type,
                                 &format!("{this}.value"),
                             ));
                         },
@@ -328,11 +440,12 @@ impl FieldGenerator<'_> {
                     json_encode.push("assert(value ~= nil, \"Luau\")");
                     json_encode.push(format!(
                         "newOutput[{}] = {}",
-                        json_key_to_string(&map_type.key).encode,
+                        json_key_to_string(&map_type.key, self.int64_type.for_map_key()).encode,
                         json_encode_instruction_field_descriptor_ignore_repeated(
                             &map_type.value,
                             self.export_map,
                             self.base_file,
+                            self.int64_type,
                             "value"
                         )
                     ));
@@ -347,6 +460,7 @@ impl FieldGenerator<'_> {
                             field,
                             self.export_map,
                             self.base_file,
+                            self.int64_type,
                             "value"
                         )
                     ));
@@ -359,6 +473,7 @@ impl FieldGenerator<'_> {
                             field,
                             self.export_map,
                             self.base_file,
+                            self.int64_type,
                             &this
                         )
                     ));
@@ -379,6 +494,7 @@ impl FieldGenerator<'_> {
                                     field,
                                     self.export_map,
                                     self.base_file,
+                                    self.int64_type,
                                     &format!("{this}.value")
                                 )
                             ));
@@ -405,6 +521,10 @@ impl FieldGenerator<'_> {
             let mut decode_name = |input_name: &str| {
                 json_decode.push(format!("if input.{input_name} ~= nil then"));
 
+                if let Some(report) = self.report_deprecated(inner_field, "decode") {
+                    json_decode.push(report);
+                }
+
                 if let Some(map_type) = self.map_type() {
                     json_decode.push(format!(
                         "local newOutput: {} = {{}}",
@@ -413,11 +533,12 @@ impl FieldGenerator<'_> {
                     json_decode.push(format!("for key, value in input.{input_name} do"));
                     json_decode.push(format!(
                         "newOutput[{}] = {}",
-                        json_key_to_string(&map_type.key).decode,
+                        json_key_to_string(&map_type.key, self.int64_type.for_map_key()).decode,
                         json_decode_instruction_field_descriptor_ignore_repeated(
                             &map_type.value,
                             self.export_map,
                             self.base_file,
+                            self.int64_type,
                             "value"
                         )
                     ));
@@ -436,6 +557,7 @@ impl FieldGenerator<'_> {
                             inner_field,
                             self.export_map,
                             self.base_file,
+                            self.int64_type,
                             "value"
                         )
                     ));
@@ -448,6 +570,7 @@ impl FieldGenerator<'_> {
                             inner_field,
                             self.export_map,
                             self.base_file,
+                            self.int64_type,
                             &format!("input.{input_name}"),
                         );
 
@@ -475,6 +598,461 @@ impl FieldGenerator<'_> {
         json_decode
     }
 
+    /// Returns false from the enclosing function if `self` and `other` differ in this field.
+    pub fn equals(&self) -> StringBuilder {
+        let name = self.name();
+        let this = format!("self.{name}");
+        let other = format!("other.{name}");
+
+        let mut equals = StringBuilder::new();
+
+        match &self.field_kind {
+            FieldKind::Single(field) => {
+                if let Some(map_type) = self.map_type() {
+                    let differs = self.differs(&map_type.value, "value", "otherValue");
+
+                    equals.push(indoc::formatdoc! {"
+                        for key, value in {this} do
+                            local otherValue = {other}[key]
+                            if otherValue == nil or {differs} then
+                                return false
+                            end
+                        end
+
+                        for key in {other} do
+                            if {this}[key] == nil then
+                                return false
+                            end
+                        end
+                    "});
+                } else if field.label() == Label::Repeated {
+                    let differs = self.differs(field, "value", &format!("{other}[index]"));
+
+                    equals.push(indoc::formatdoc! {"
+                        if #{this} ~= #{other} then
+                            return false
+                        end
+
+                        for index, value in {this} do
+                            if {differs} then
+                                return false
+                            end
+                        end
+                    "});
+                } else if self.has_presence() && needs_deep_equals(field) {
+                    let differs = self.differs(field, &this, &other);
+
+                    equals.push(indoc::formatdoc! {"
+                        if {this} == nil or {other} == nil then
+                            if {this} ~= {other} then
+                                return false
+                            end
+                        elseif {differs} then
+                            return false
+                        end
+                    "});
+                } else {
+                    equals.push(format!(
+                        "if {} then return false end",
+                        self.differs(field, &this, &other)
+                    ));
+                }
+            }
+
+            FieldKind::OneOf { fields, .. } => {
+                equals.push(indoc::formatdoc! {"
+                    do
+                        local value, otherValue = {this}, {other}
+                        if value == nil or otherValue == nil then
+                            if value ~= otherValue then
+                                return false
+                            end
+                        elseif value.type ~= otherValue.type then
+                            return false
+                        end
+                "});
+
+                let mut if_builder = IfBuilder::new();
+
+                for field in fields {
+                    if_builder.add_condition(
+                        &format!(
+                            "value ~= nil and value.type == \"{}\" and {}",
+                            self.luau_name(field.name()),
+                            self.differs(field, "value.value", "(otherValue :: any).value")
+                        ),
+                        |builder| builder.push("return false"),
+                    );
+                }
+
+                equals.append(&if_builder.into_string_builder());
+                equals.push("end");
+            }
+        }
+
+        equals
+    }
+
+    /// Sets this field of `copy` to a deep copy of the one in `self`.
+    pub fn deep_clone(&self) -> StringBuilder {
+        let name = self.name();
+        let this = format!("self.{name}");
+        let copy = format!("copy.{name}");
+
+        let mut clone = StringBuilder::new();
+
+        match &self.field_kind {
+            FieldKind::Single(field) => {
+                if let Some(map_type) = self.map_type() {
+                    if needs_deep_clone(&map_type.value) {
+                        clone.push(format!("for key, value in {this} do"));
+                        clone.push(format!(
+                            "{copy}[key] = {}",
+                            clone_instruction_field_descriptor_ignore_repeated(
+                                &map_type.value,
+                                "value"
+                            )
+                        ));
+                        clone.push("end");
+                    } else {
+                        clone.push(format!("{copy} = table.clone({this})"));
+                    }
+                } else if field.label() == Label::Repeated {
+                    if needs_deep_clone(field) {
+                        clone.push(format!("for _, value in {this} do"));
+                        clone.push(format!(
+                            "table.insert({copy}, {})",
+                            clone_instruction_field_descriptor_ignore_repeated(field, "value")
+                        ));
+                        clone.push("end");
+                    } else {
+                        clone.push(format!("{copy} = table.clone({this})"));
+                    }
+                } else if self.has_presence() && needs_deep_clone(field) {
+                    clone.push(format!(
+                        "{copy} = if {this} == nil then nil else {}",
+                        clone_instruction_field_descriptor_ignore_repeated(field, &this)
+                    ));
+                } else {
+                    clone.push(format!(
+                        "{copy} = {}",
+                        clone_instruction_field_descriptor_ignore_repeated(field, &this)
+                    ));
+                }
+            }
+
+            FieldKind::OneOf { .. } => {
+                clone.push("do");
+                clone.push(format!("local value = {this}"));
+                clone.push("if value ~= nil then");
+                clone.append(&self.clone_oneof("value", &copy));
+                clone.push("end");
+                clone.push("end");
+            }
+        }
+
+        clone
+    }
+
+    /// Merges this field of `other` into `self`. Set scalars overwrite, repeated fields append,
+    /// maps take the other's entries, messages merge recursively, and oneofs replace.
+    pub fn merge_from(&self) -> StringBuilder {
+        let name = self.name();
+        let this = format!("self.{name}");
+        let other = format!("other.{name}");
+
+        let mut merge = StringBuilder::new();
+
+        match &self.field_kind {
+            FieldKind::Single(field) => {
+                if let Some(map_type) = self.map_type() {
+                    merge.push(format!("for key, value in {other} do"));
+                    merge.push(format!(
+                        "{this}[key] = {}",
+                        clone_instruction_field_descriptor_ignore_repeated(
+                            &map_type.value,
+                            "value"
+                        )
+                    ));
+                    merge.push("end");
+                } else if field.label() == Label::Repeated {
+                    merge.push(format!("for _, value in {other} do"));
+                    merge.push(format!(
+                        "table.insert({this}, {})",
+                        clone_instruction_field_descriptor_ignore_repeated(field, "value")
+                    ));
+                    merge.push("end");
+                } else if matches!(field.r#type(), Type::Message | Type::Group) {
+                    merge.push(indoc::formatdoc! {"
+                        do
+                            local value, otherValue = {this}, {other}
+                            if otherValue ~= nil then
+                                if value == nil then
+                                    {this} = otherValue:clone()
+                                else
+                                    value:mergeFrom(otherValue)
+                                end
+                            end
+                        end
+                    "});
+                } else {
+                    merge.push(format!("if {} then", self.is_set(&other)));
+                    merge.push(format!(
+                        "{this} = {}",
+                        clone_instruction_field_descriptor_ignore_repeated(field, &other)
+                    ));
+                    merge.push("end");
+                }
+            }
+
+            FieldKind::OneOf { fields, .. } => {
+                merge.push("do");
+                merge.push(format!("local value, otherValue = {this}, {other}"));
+                merge.push("if otherValue ~= nil then");
+
+                // Messages in the same case merge, everything else replaces what was there.
+                let mut if_builder = IfBuilder::new();
+                for field in fields {
+                    if !matches!(field.r#type(), Type::Message | Type::Group) {
+                        continue;
+                    }
+
+                    let variant = self.luau_name(field.name());
+                    if_builder.add_condition(
+                        &format!(
+                            "value ~= nil and value.type == \"{variant}\" and otherValue.type == \"{variant}\""
+                        ),
+                        |builder| builder.push("value.value:mergeFrom((otherValue :: any).value)"),
+                    );
+                }
+
+                if if_builder.is_empty() {
+                    merge.append(&self.clone_oneof("otherValue", &this));
+                } else {
+                    merge.append(
+                        &if_builder
+                            .with_else(|builder| {
+                                builder.append(&self.clone_oneof("otherValue", &this))
+                            })
+                            .into_string_builder(),
+                    );
+                }
+
+                merge.push("end");
+                merge.push("end");
+            }
+        }
+
+        merge
+    }
+
+    /// Sets `output` to a deep copy of the oneof value in the local `value`, which isn't nil.
+    fn clone_oneof(&self, value: &str, output: &str) -> StringBuilder {
+        let FieldKind::OneOf { fields, .. } = &self.field_kind else {
+            unreachable!("clone_oneof is only for oneofs");
+        };
+
+        let mut if_builder = IfBuilder::new();
+        for field in fields {
+            if !needs_deep_clone(field) {
+                continue;
+            }
+
+            let variant = self.luau_name(field.name());
+            if_builder.add_condition(&format!("{value}.type == \"{variant}\""), |builder| {
+                builder.push(format!(
+                    "{output} = {{ type = \"{variant}\", value = {} }}",
+                    clone_instruction_field_descriptor_ignore_repeated(
+                        field,
+                        &format!("{value}.value")
+                    )
+                ))
+            });
+        }
+
+        let copy = format!("{output} = {{ type = {value}.type, value = {value}.value }} :: any");
+
+        if if_builder.is_empty() {
+            StringBuilder::from(copy)
+        } else {
+            if_builder
+                .with_else(|builder| builder.push(copy))
+                .into_string_builder()
+        }
+    }
+
+    /// A Luau condition for whether two values of the field, ignoring repetition, are different.
+    fn differs(&self, field: &FieldDescriptorProto, a: &str, b: &str) -> String {
+        match field.r#type() {
+            Type::Message | Type::Group => format!("not {a}:equals({b})"),
+            Type::Bytes => format!("buffer.tostring({a}) ~= buffer.tostring({b})"),
+
+            // Aliases are different names for the same number.
+            Type::Enum => {
+                let enum_type =
+                    runtime_definition_of_field_descriptor(field, self.export_map, self.base_file);
+
+                format!(
+                    "({a} ~= {b} and {enum_type}.toNumber({a} :: any) ~= {enum_type}.toNumber({b} :: any))"
+                )
+            }
+
+            _ => format!("{a} ~= {b}"),
+        }
+    }
+
+    /// Luau `proto.FieldDescriptor` tables for every field this generates, with their field numbers.
+    pub fn descriptors(
+        &self,
+        oneof_names: &[&str],
+        options_map: &OptionsMap,
+        message_full_name: &str,
+    ) -> Vec<(i32, String)> {
+        // Entries only some fields have, after the ones every field has.
+        let optional_entries = |field: &FieldDescriptorProto, oneof: Option<&str>| {
+            let mut entries = Vec::new();
+
+            if let Some(oneof) = oneof {
+                entries.push(format!("oneof = \"{oneof}\""));
+            }
+
+            if let Some(custom_options) =
+                options_map.custom_options(&format!("{message_full_name}.{}", field.name()))
+            {
+                entries.push(format!("options = {custom_options}"));
+            }
+
+            if let Some(default) = custom_default_of_field_descriptor(field, self.int64_type) {
+                entries.push(format!("default = {default}"));
+            }
+
+            if is_deprecated(field) {
+                entries.push("deprecated = true".to_owned());
+            }
+
+            entries
+        };
+
+        match &self.field_kind {
+            FieldKind::Single(field) => {
+                // proto3 optional fields are in a synthetic oneof, which is an implementation detail.
+                let oneof = if field.proto3_optional() {
+                    None
+                } else {
+                    field
+                        .oneof_index
+                        .and_then(|index| oneof_names.get(index as usize).copied())
+                };
+
+                let mut entries = optional_entries(field, oneof);
+
+                if let Some(map_type) = self.map_type() {
+                    entries.push(format!(
+                        "map = {{ key = {}, value = {} }}",
+                        self.field_descriptor(&map_type.key, "key", "optional", false, Vec::new()),
+                        self.field_descriptor(
+                            &map_type.value,
+                            "value",
+                            "optional",
+                            matches!(map_type.value.r#type(), Type::Message | Type::Group),
+                            Vec::new(),
+                        ),
+                    ));
+                }
+
+                vec![(
+                    field.number(),
+                    self.field_descriptor(
+                        field,
+                        &self.name(),
+                        self.label(field),
+                        self.has_presence(),
+                        entries,
+                    ),
+                )]
+            }
+
+            FieldKind::OneOf { name, fields } => fields
+                .iter()
+                .map(|field| {
+                    (
+                        field.number(),
+                        self.field_descriptor(
+                            field,
+                            &self.luau_name(field.name()),
+                            "optional",
+                            true,
+                            optional_entries(field, Some(name)),
+                        ),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn label(&self, field: &FieldDescriptorProto) -> &'static str {
+        if field.label() == Label::Repeated {
+            "repeated"
+        } else if self.is_required() {
+            "required"
+        } else {
+            "optional"
+        }
+    }
+
+    fn field_descriptor(
+        &self,
+        field: &FieldDescriptorProto,
+        luau_name: &str,
+        label: &str,
+        has_presence: bool,
+        optional_entries: Vec<String>,
+    ) -> String {
+        let mut entries = vec![
+            format!("name = \"{}\"", field.name()),
+            format!("luauName = \"{luau_name}\""),
+            format!("jsonName = \"{}\"", json_name(field)),
+            format!("number = {}", field.number()),
+            format!(
+                "type = \"{}\"",
+                field
+                    .r#type()
+                    .as_str_name()
+                    .trim_start_matches("TYPE_")
+                    .to_lowercase()
+            ),
+            format!("label = \"{label}\""),
+            format!("hasPresence = {has_presence}"),
+        ];
+
+        if !field.type_name().is_empty() {
+            entries.push(format!(
+                "typeName = \"{}\"",
+                field.type_name().trim_start_matches('.')
+            ));
+        }
+
+        entries.extend(optional_entries);
+
+        format!("{{ {} }}", entries.join(", "))
+    }
+
+    /// Whether the whole field is deprecated. Oneofs can only have deprecated members.
+    pub fn is_deprecated(&self) -> bool {
+        matches!(&self.field_kind, FieldKind::Single(field) if is_deprecated(field))
+    }
+
+    /// A call passing `field` to the deprecation handler, if it's deprecated.
+    pub fn report_deprecated(&self, field: &FieldDescriptorProto, usage: &str) -> Option<String> {
+        is_deprecated(field).then(|| {
+            format!(
+                r#"proto.reportDeprecated("{}.{}", "{usage}")"#,
+                self.scope.trim_start_matches('.'),
+                field.name()
+            )
+        })
+    }
+
     pub fn inner_fields(&self) -> Vec<&FieldDescriptorProto> {
         match &self.field_kind {
             FieldKind::Single(field) => vec![field],
@@ -505,15 +1083,23 @@ impl FieldGenerator<'_> {
         export.map.as_ref()
     }
 
+    /// The value the field reads as while it's unset, which is its proto2 `[default = ...]` if it has one.
     pub fn default(&self) -> Cow<'static, str> {
+        if let Some(default) = self.custom_default() {
+            return default.into();
+        }
+
         if self.has_presence() {
             return "nil".into();
         }
 
         match self.field_kind {
-            FieldKind::Single(field) => {
-                default_of_type_descriptor_ignore_presence(field, self.export_map, self.base_file)
-            }
+            FieldKind::Single(field) => default_of_type_descriptor_ignore_presence(
+                field,
+                self.export_map,
+                self.base_file,
+                self.int64_type,
+            ),
 
             FieldKind::OneOf { .. } => "nil".into(),
         }
@@ -525,70 +1111,99 @@ fn definition_of_field_descriptor(
     export_map: &ExportMap,
     base_file: &FileDescriptorProto,
     local_scope_prefix: &str,
+    int64_type: Int64Type,
 ) -> String {
     match field.r#type() {
+        Type::Int64 | Type::Uint64 | Type::Fixed64 | Type::Sint64 | Type::Sfixed64 => {
+            match int64_type {
+                Int64Type::Number => "number".to_owned(),
+                Int64Type::String => "string".to_owned(),
+                Int64Type::Int64 => "proto.Int64".to_owned(),
+            }
+        }
         Type::Int32
         | Type::Uint32
-        | Type::Int64
-        | Type::Uint64
         | Type::Fixed32
-        | Type::Fixed64
         | Type::Sint32
-        | Type::Sint64
         | Type::Sfixed32
-        | Type::Sfixed64
         | Type::Float
         | Type::Double => "number".to_owned(),
         Type::String => "string".to_owned(),
         Type::Bool => "boolean".to_owned(),
         Type::Bytes => "buffer".to_owned(),
-        Type::Enum | Type::Message => {
-            let original_type_name = field.type_name();
-            assert!(
-                original_type_name.starts_with('.'),
-                "NYI: Relative type names: {original_type_name}"
-            );
+        Type::Enum | Type::Message | Type::Group => {
+            definition_of_type_name(field.type_name(), export_map, base_file, local_scope_prefix)
+        }
+    }
+}
 
-            let type_name = &original_type_name[1..];
+fn definition_of_type_name(
+    original_type_name: &str,
+    export_map: &ExportMap,
+    base_file: &FileDescriptorProto,
+    local_scope_prefix: &str,
+) -> String {
+    assert!(
+        original_type_name.starts_with('.'),
+        "NYI: Relative type names: {original_type_name}"
+    );
 
-            let mut segments: Vec<&str> = type_name.split('.').collect();
-            let just_type = segments.pop().unwrap();
-            let package = segments.join(".");
+    let type_name = &original_type_name[1..];
 
-            let export = export_map
-                .get(&format!("{package}.{just_type}"))
-                .or_else(|| export_map.get(original_type_name))
-                .unwrap_or_else(|| panic!("couldn't find export {package}.{just_type}"));
+    let mut segments: Vec<&str> = type_name.split('.').collect();
+    let just_type = segments.pop().unwrap();
+    let package = segments.join(".");
 
-            if export.path == Path::new(base_file.name()).with_extension("") {
-                format!("{local_scope_prefix}{}{just_type}", export.prefix)
-            } else {
-                format!(
-                    "{}.{}{just_type}",
-                    file_path_export_name(&export.path),
-                    export.prefix,
-                )
-            }
-        }
+    let export = export_map
+        .get(&format!("{package}.{just_type}"))
+        .or_else(|| export_map.get(original_type_name))
+        .unwrap_or_else(|| panic!("couldn't find export {package}.{just_type}"));
 
-        Type::Group => unimplemented!("Group"),
+    if export.path == Path::new(base_file.name()).with_extension("") {
+        format!("{local_scope_prefix}{}{just_type}", export.prefix)
+    } else {
+        format!(
+            "{}.{}{just_type}",
+            file_path_export_name(&export.path),
+            export.prefix,
+        )
     }
 }
 
+/// The Luau expression for the runtime table of a message or enum, such as `messages.MyMessage`.
+pub fn runtime_definition_of_type_name(
+    type_name: &str,
+    export_map: &ExportMap,
+    base_file: &FileDescriptorProto,
+) -> String {
+    definition_of_type_name(type_name, export_map, base_file, "messages.")
+}
+
+/// The Luau type of a message or enum, such as `MyMessage`.
+pub fn type_definition_of_type_name(
+    type_name: &str,
+    export_map: &ExportMap,
+    base_file: &FileDescriptorProto,
+) -> String {
+    definition_of_type_name(type_name, export_map, base_file, "")
+}
+
 fn runtime_definition_of_field_descriptor(
     field: &FieldDescriptorProto,
     export_map: &ExportMap,
     base_file: &FileDescriptorProto,
 ) -> String {
-    definition_of_field_descriptor(field, export_map, base_file, "messages.")
+    // Only used for enums and messages, so the integer representation doesn't matter.
+    definition_of_field_descriptor(field, export_map, base_file, "messages.", Int64Type::Number)
 }
 
 fn type_definition_of_field_descriptor(
     field: &FieldDescriptorProto,
     export_map: &ExportMap,
     base_file: &FileDescriptorProto,
+    int64_type: Int64Type,
 ) -> String {
-    definition_of_field_descriptor(field, export_map, base_file, "")
+    definition_of_field_descriptor(field, export_map, base_file, "", int64_type)
 }
 
 #[derive(Clone, Copy)]
@@ -597,6 +1212,7 @@ pub enum WireType {
     LengthDelimited,
     I32,
     I64,
+    StartGroup,
 }
 
 pub fn wire_type_of_field_descriptor(field: &FieldDescriptorProto) -> WireType {
@@ -612,7 +1228,7 @@ pub fn wire_type_of_field_descriptor(field: &FieldDescriptorProto) -> WireType {
         Type::Float | Type::Fixed32 | Type::Sfixed32 => WireType::I32,
         Type::Double | Type::Fixed64 | Type::Sfixed64 => WireType::I64,
         Type::String | Type::Bytes | Type::Message => WireType::LengthDelimited,
-        Type::Group => unimplemented!("Group"),
+        Type::Group => WireType::StartGroup,
     }
 }
 
@@ -620,8 +1236,26 @@ fn encode_field_descriptor_ignore_repeated_instruction(
     field: &FieldDescriptorProto,
     export_map: &ExportMap,
     base_file: &FileDescriptorProto,
+    int64_type: Int64Type,
     value_var: &str,
 ) -> String {
+    if let Some(kind) = int64_kind(field) {
+        match int64_type {
+            Int64Type::Number => {}
+            Int64Type::String => {
+                return format!(
+                    "output, cursor = proto.writeInt64(output, cursor, \"{kind}\", proto.Int64.fromString({value_var}, {}))",
+                    is_unsigned_64(field)
+                );
+            }
+            Int64Type::Int64 => {
+                return format!(
+                    "output, cursor = proto.writeInt64(output, cursor, \"{kind}\", {value_var})"
+                );
+            }
+        }
+    }
+
     match field.r#type() {
         Type::Int32 | Type::Uint32 | Type::Int64 | Type::Uint64 => {
             format!("output, cursor = proto.writeVarInt(output, cursor, {value_var})")
@@ -649,16 +1283,19 @@ fn encode_field_descriptor_ignore_repeated_instruction(
             runtime_definition_of_field_descriptor(field, export_map, base_file)
         ),
 
-        Type::Message => unimplemented!(),
+        Type::Message | Type::Group => {
+            unreachable!("Messages are written by encode_field_descriptor_ignore_repeated")
+        }
 
         Type::Fixed32 | Type::Sfixed32 => {
             format!("output, cursor = proto.writeFixed32(output, cursor, {value_var})")
         }
-        Type::Fixed64 | Type::Sfixed64 => {
+        Type::Fixed64 => {
             format!("output, cursor = proto.writeFixed64(output, cursor, {value_var})")
         }
-
-        Type::Group => unimplemented!("Group"),
+        Type::Sfixed64 => {
+            format!("output, cursor = proto.writeSignedFixed64(output, cursor, {value_var})")
+        }
     }
 }
 
@@ -666,6 +1303,7 @@ fn encode_field_descriptor_ignore_repeated(
     field: &FieldDescriptorProto,
     export_map: &ExportMap,
     base_file: &FileDescriptorProto,
+    int64_type: Int64Type,
     value_var: &str,
 ) -> String {
     if field.r#type() == Type::Message {
@@ -680,6 +1318,17 @@ fn encode_field_descriptor_ignore_repeated(
         .join("\n");
     }
 
+    if field.r#type() == Type::Group {
+        return [
+            format!("local encoded = {value_var}:encode()"),
+            format!(
+                "output, cursor = proto.writeGroup(output, cursor, {}, encoded)",
+                field.number()
+            ),
+        ]
+        .join("\n");
+    }
+
     let setup = match wire_type_of_field_descriptor(field) {
         WireType::Varint => format!(
             "output, cursor = proto.writeTag(output, cursor, {}, proto.wireTypes.varint)",
@@ -697,12 +1346,13 @@ fn encode_field_descriptor_ignore_repeated(
             "output, cursor = proto.writeTag(output, cursor, {}, proto.wireTypes.i64)",
             field.number()
         ),
+        WireType::StartGroup => unreachable!("Groups are written above"),
     };
 
     format!(
         "{setup}\n{}",
         encode_field_descriptor_ignore_repeated_instruction(
-            field, export_map, base_file, value_var
+            field, export_map, base_file, int64_type, value_var
         )
     )
 }
@@ -711,8 +1361,13 @@ fn json_encode_instruction_field_descriptor_ignore_repeated(
     field: &FieldDescriptorProto,
     export_map: &ExportMap,
     base_file: &FileDescriptorProto,
+    int64_type: Int64Type,
     value_var: &str,
 ) -> String {
+    if int64_type == Int64Type::Int64 && int64_kind(field).is_some() {
+        return format!("tostring({value_var})");
+    }
+
     match field.r#type() {
         Type::Int32
         | Type::Int64
@@ -733,8 +1388,7 @@ fn json_encode_instruction_field_descriptor_ignore_repeated(
             "if typeof({value_var}) == \"number\" then {value_var} else {}.toNumber({value_var} :: any)",
             runtime_definition_of_field_descriptor(field, export_map, base_file)
         ),
-        Type::Message => format!("{value_var}:jsonEncode()"),
-        Type::Group => unimplemented!("Group"),
+        Type::Message | Type::Group => format!("{value_var}:jsonEncode()"),
     }
 }
 
@@ -742,8 +1396,25 @@ fn json_decode_instruction_field_descriptor_ignore_repeated(
     field: &FieldDescriptorProto,
     export_map: &ExportMap,
     base_file: &FileDescriptorProto,
+    int64_type: Int64Type,
     value_var: &str,
 ) -> String {
+    if int64_kind(field).is_some() {
+        let unsigned = is_unsigned_64(field);
+
+        match int64_type {
+            Int64Type::Number => {}
+            Int64Type::String => {
+                return format!(
+                    "tostring(proto.json.deserializeExactInt64({value_var}, {unsigned}))"
+                );
+            }
+            Int64Type::Int64 => {
+                return format!("proto.json.deserializeExactInt64({value_var}, {unsigned})");
+            }
+        }
+    }
+
     match field.r#type() {
         Type::Int32
         | Type::Uint32
@@ -762,28 +1433,38 @@ fn json_decode_instruction_field_descriptor_ignore_repeated(
                 or {value_var}) else ({qualified_enum}.fromName({value_var}) or {value_var})",
             qualified_enum = runtime_definition_of_field_descriptor(field, export_map, base_file)
         ),
-        Type::Message => format!(
+        Type::Message | Type::Group => format!(
             "{}.jsonDecode({value_var})",
             runtime_definition_of_field_descriptor(field, export_map, base_file)
         ),
-        Type::Group => unimplemented!("Group"),
     }
 }
 
 struct JsonKeyToString {
     encode: &'static str,
-    decode: &'static str,
+    decode: Cow<'static, str>,
 }
-fn json_key_to_string(field: &FieldDescriptorProto) -> JsonKeyToString {
+fn json_key_to_string(field: &FieldDescriptorProto, int64_type: Int64Type) -> JsonKeyToString {
+    if int64_type == Int64Type::String && int64_kind(field).is_some() {
+        return JsonKeyToString {
+            encode: "key",
+            decode: format!(
+                "tostring(proto.json.deserializeExactInt64(key, {}))",
+                is_unsigned_64(field)
+            )
+            .into(),
+        };
+    }
+
     match field.r#type() {
         Type::Bool => JsonKeyToString {
             encode: "tostring(key)",
-            decode: "if key == \"true\" then true else false",
+            decode: "if key == \"true\" then true else false".into(),
         },
 
         Type::String => JsonKeyToString {
             encode: "key",
-            decode: "key",
+            decode: "key".into(),
         },
 
         Type::Int32
@@ -797,7 +1478,7 @@ fn json_key_to_string(field: &FieldDescriptorProto) -> JsonKeyToString {
         | Type::Sfixed32
         | Type::Sfixed64 => JsonKeyToString {
             encode: "tostring(key)",
-            decode: "(assert(tonumber(key), \"Invalid number provided as key\"))",
+            decode: "(assert(tonumber(key), \"Invalid number provided as key\"))".into(),
         },
 
         Type::Double | Type::Float | Type::Group | Type::Message | Type::Bytes | Type::Enum => {
@@ -818,39 +1499,53 @@ fn default_of_type_descriptor_ignore_presence(
     field: &FieldDescriptorProto,
     export_map: &ExportMap,
     base_file: &FileDescriptorProto,
+    int64_type: Int64Type,
 ) -> Cow<'static, str> {
     if field.label.is_some() && field.label() == Label::Repeated {
         return "{}".into();
     }
 
     match field.r#type() {
+        Type::Int64 | Type::Uint64 | Type::Fixed64 | Type::Sint64 | Type::Sfixed64 => {
+            match int64_type {
+                Int64Type::Number => "0".into(),
+                Int64Type::String => "\"0\"".into(),
+                Int64Type::Int64 if is_unsigned_64(field) => "proto.Int64.new(0, 0, true)".into(),
+                Int64Type::Int64 => "proto.Int64.new(0, 0)".into(),
+            }
+        }
         Type::Int32
         | Type::Uint32
-        | Type::Int64
-        | Type::Uint64
         | Type::Fixed32
-        | Type::Fixed64
         | Type::Sint32
-        | Type::Sint64
         | Type::Sfixed32
-        | Type::Sfixed64
         | Type::Float
         | Type::Double => "0".into(),
         Type::String => "\"\"".into(),
         Type::Bool => "false".into(),
         Type::Bytes => "buffer.create(0)".into(),
         // proto2: Enums default to first value
-        Type::Enum => format!(
-            "assert({}.fromNumber(0), \"Enum has no 0 default\")",
-            runtime_definition_of_field_descriptor(field, export_map, base_file)
-        )
-        .into(),
-        Type::Message => format!(
+        Type::Enum => match enum_type_of_field_descriptor(field, export_map) {
+            Some(EnumType {
+                closed: true,
+                first_value,
+            }) => format!(
+                "assert({}.fromName(\"{first_value}\"), \"Enum has no default\")",
+                runtime_definition_of_field_descriptor(field, export_map, base_file)
+            )
+            .into(),
+
+            _ => format!(
+                "assert({}.fromNumber(0), \"Enum has no 0 default\")",
+                runtime_definition_of_field_descriptor(field, export_map, base_file)
+            )
+            .into(),
+        },
+        Type::Message | Type::Group => format!(
             "{}.new()",
             runtime_definition_of_field_descriptor(field, export_map, base_file)
         )
         .into(),
-        Type::Group => unimplemented!("Group"),
     }
 }
 
@@ -858,7 +1553,13 @@ fn decode_instruction_field_descriptor_ignore_repeated(
     field: &FieldDescriptorProto,
     export_map: &ExportMap,
     base_file: &FileDescriptorProto,
+    utf8_validation: Utf8Validation,
+    int64_type: Int64Type,
 ) -> Cow<'static, str> {
+    if int64_type == Int64Type::String && int64_kind(field).is_some() {
+        return "tostring(value)".into();
+    }
+
     match field.r#type() {
 		Type::Int32
         | Type::Uint32
@@ -876,7 +1577,10 @@ fn decode_instruction_field_descriptor_ignore_repeated(
 
         Type::Bool => "value ~= 0".into(),
 
-        Type::String => "proto.decodeString(value)".into(),
+        Type::String => match utf8_validation {
+            Utf8Validation::Verify => "proto.decodeString(value)".into(),
+            Utf8Validation::None => "buffer.tostring(value)".into(),
+        },
 
         Type::Enum => format!(
             "({}.fromNumber(value) or value) :: any --[[ Luau: Enums are a string intersection which Luau is quick to dismantle ]]",
@@ -884,154 +1588,296 @@ fn decode_instruction_field_descriptor_ignore_repeated(
         )
         .into(),
 
-        Type::Message => format!(
-            "{}.decode(value)",
+        Type::Message | Type::Group => format!(
+            "{}.decode(value, limits)",
             runtime_definition_of_field_descriptor(field, export_map, base_file)
         )
         .into(),
-
-        Type::Group => unimplemented!("Group"),
     }
 }
 
-// TODO: Variable for "value" instead of replace
-pub fn decode_field(
-    this: &str,
-    field: &FieldDescriptorProto,
-    export_map: &ExportMap,
-    base_file: &FileDescriptorProto,
-    map_type: Option<&MapType>,
-    is_oneof: bool,
-    field_name_case: FieldNameCase,
-) -> StringBuilder {
-    let mut decode = StringBuilder::new();
-
-    if let Some(map_type) = map_type {
-        let map_entry_type = runtime_definition_of_field_descriptor(field, export_map, base_file);
+impl FieldGenerator<'_> {
+    // TODO: Variable for "value" instead of replace
+    pub fn decode(&self, this: &str, field: &FieldDescriptorProto) -> StringBuilder {
+        let export_map = self.export_map;
+        let base_file = self.base_file;
+        let is_oneof = matches!(self.field_kind, FieldKind::OneOf { .. });
+
+        let mut decode = StringBuilder::new();
+        let field_name = field.name();
+
+        if let Some(map_type) = self.map_type() {
+            let map_entry_type =
+                runtime_definition_of_field_descriptor(field, export_map, base_file);
+
+            let key_default = default_of_type_descriptor_ignore_presence(
+                &map_type.key,
+                export_map,
+                base_file,
+                self.int64_type.for_map_key(),
+            );
 
-        let key_default =
-            default_of_type_descriptor_ignore_presence(&map_type.key, export_map, base_file);
+            let value_default = default_of_type_descriptor_ignore_presence(
+                &map_type.value,
+                export_map,
+                base_file,
+                self.int64_type,
+            );
 
-        let value_default =
-            default_of_type_descriptor_ignore_presence(&map_type.value, export_map, base_file);
+            // TODO: Type keyDefault and valueDefault
+            decode.push(indoc::formatdoc! {"
+                if limits ~= nil then
+                    proto.checkMapEntries(limits, \"{field_name}\")
+                end
 
-        // TODO: Type keyDefault and valueDefault
-        decode.push(indoc::formatdoc! {"
-            local value
-            value, cursor = proto.readBuffer(input, cursor)
+                local value
+                value, cursor = proto.readBuffer(input, cursor)
 
-            local mapEntry = {map_entry_type}.decode(value)
+                local mapEntry = {map_entry_type}.decode(value, limits)
 
-            local keyDefault = {key_default}
-            local valueDefault = {value_default}
+                local keyDefault = {key_default}
+                local valueDefault = {value_default}
 
-            {this}[mapEntry.key or keyDefault] = mapEntry.value or valueDefault
-        "})
-    } else {
-        match field.r#type() {
-            Type::Float => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readFloat(input, cursor)");
+                {this}[mapEntry.key or keyDefault] = mapEntry.value or valueDefault
+            "})
+        } else {
+            if field.label.is_some() && field.label() == Label::Repeated {
+                decode.push(indoc::formatdoc! {"
+                    if limits ~= nil then
+                        proto.checkRepeated(limits, \"{field_name}\")
+                    end
+                "});
             }
 
-            Type::Double => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readDouble(input, cursor)");
-            }
+            match field.r#type() {
+                Type::Int64 | Type::Uint64 | Type::Sint64 | Type::Fixed64 | Type::Sfixed64
+                    if self.int64_type != Int64Type::Number =>
+                {
+                    decode.push("local value");
+                    decode.push(format!(
+                        "value, cursor = proto.readInt64(input, cursor, \"{}\")",
+                        int64_kind(field).unwrap()
+                    ));
+                }
 
-            Type::Fixed32 => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readFixed32(input, cursor)");
-            }
+                Type::Float => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readFloat(input, cursor)");
+                }
 
-            Type::Fixed64 => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readFixed64(input, cursor)");
-            }
+                Type::Double => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readDouble(input, cursor)");
+                }
 
-            Type::Sfixed32 => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readSignedFixed32(input, cursor)");
-            }
+                Type::Fixed32 => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readFixed32(input, cursor)");
+                }
 
-            Type::Sfixed64 => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readSignedFixed64(input, cursor)");
-            }
+                Type::Fixed64 => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readFixed64(input, cursor)");
+                }
 
-            Type::Uint64 => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readVarIntU64(input, cursor)");
-            }
+                Type::Sfixed32 => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readSignedFixed32(input, cursor)");
+                }
 
-            Type::Uint32 => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readVarIntU32(input, cursor)");
-            }
+                Type::Sfixed64 => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readSignedFixed64(input, cursor)");
+                }
 
-            Type::Int64 => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readVarIntI64(input, cursor)");
-            }
+                Type::Uint64 => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readVarIntU64(input, cursor)");
+                }
 
-            Type::Int32 | Type::Enum => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readVarIntI32(input, cursor)");
-            }
+                Type::Uint32 => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readVarIntU32(input, cursor)");
+                }
 
-            Type::Sint64 => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readVarIntS64(input, cursor)");
-            }
+                Type::Int64 => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readVarIntI64(input, cursor)");
+                }
 
-            Type::Sint32 => {
-                decode.push("local value");
-                decode.push("value, cursor = proto.readVarIntS32(input, cursor)");
-            }
+                Type::Int32 | Type::Enum => {
+                    decode.push("local value");
+                    decode.push("value, cursor = proto.readVarIntI32(input, cursor)");
+                }
 
-            _ => match wire_type_of_field_descriptor(field) {
-                WireType::Varint => {
+                Type::Sint64 => {
                     decode.push("local value");
-                    decode.push("value, cursor = proto.readVarInt(input, cursor)");
+                    decode.push("value, cursor = proto.readVarIntS64(input, cursor)");
                 }
 
-                WireType::LengthDelimited => {
+                Type::Sint32 => {
                     decode.push("local value");
-                    decode.push("value, cursor = proto.readBuffer(input, cursor)");
+                    decode.push("value, cursor = proto.readVarIntS32(input, cursor)");
                 }
 
-                WireType::I32 | WireType::I64 => {}
-            },
-        }
+                _ => match wire_type_of_field_descriptor(field) {
+                    WireType::Varint => {
+                        decode.push("local value");
+                        decode.push("value, cursor = proto.readVarInt(input, cursor)");
+                    }
 
-        if field.label.is_some() && field.label() == Label::Repeated {
-            decode.push(format!(
-                "table.insert({this}, {})",
-                decode_instruction_field_descriptor_ignore_repeated(field, export_map, base_file)
-            ));
-        } else if is_oneof {
-            decode.push(format!(
-                "{this} = {{ type = \"{}\", value = {} }}",
-                field_name_case.apply(field.name()),
-                decode_instruction_field_descriptor_ignore_repeated(field, export_map, base_file)
-            ));
-        } else {
-            decode.push(format!(
-                "{this} = {}",
-                decode_instruction_field_descriptor_ignore_repeated(field, export_map, base_file)
-            ));
+                    WireType::LengthDelimited => {
+                        decode.push("local value");
+                        decode.push("value, cursor = proto.readBuffer(input, cursor)");
+                    }
+
+                    WireType::StartGroup => {
+                        decode.push("local value");
+                        decode.push(format!(
+                            "value, cursor = proto.readGroup(input, cursor, {}, limits)",
+                            field.number()
+                        ));
+                    }
+
+                    WireType::I32 | WireType::I64 => {}
+                },
+            }
+
+            // Closed enums don't hold values they don't know about. They're kept as unknown
+            // fields if those are preserved, and dropped otherwise.
+            let is_closed_enum = field.r#type() == Type::Enum
+                && enum_type_of_field_descriptor(field, export_map)
+                    .is_some_and(|enum_type| enum_type.closed);
+
+            let decoded = if is_closed_enum {
+                decode.push(format!(
+                    "local variant = {}.fromNumber(value)",
+                    runtime_definition_of_field_descriptor(field, export_map, base_file)
+                ));
+                decode.push("if variant ~= nil then");
+                decode.indent();
+                "variant".into()
+            } else {
+                decode_instruction_field_descriptor_ignore_repeated(
+                    field,
+                    export_map,
+                    base_file,
+                    self.features_of(field).utf8_validation,
+                    self.int64_type,
+                )
+            };
+
+            if field.label.is_some() && field.label() == Label::Repeated {
+                decode.push(format!("table.insert({this}, {decoded})"));
+            } else if matches!(field.r#type(), Type::Message | Type::Group) {
+                // A message that shows up again is merged into the one already there.
+                let message_type =
+                    runtime_definition_of_field_descriptor(field, export_map, base_file);
+
+                if is_oneof {
+                    let variant = self.field_name_case.apply(field.name());
+
+                    decode.push(indoc::formatdoc! {"
+                        local existing = {this}
+                        if existing ~= nil and existing.type == \"{variant}\" then
+                            {message_type}.decodeInto(existing.value :: any, value, limits)
+                        else
+                            {this} = {{ type = \"{variant}\", value = {decoded} }}
+                        end
+                    "});
+                } else {
+                    decode.push(indoc::formatdoc! {"
+                        local existing = {this}
+                        if existing ~= nil then
+                            {message_type}.decodeInto(existing, value, limits)
+                        else
+                            {this} = {decoded}
+                        end
+                    "});
+                }
+            } else if is_oneof {
+                decode.push(format!(
+                    "{this} = {{ type = \"{}\", value = {decoded} }}",
+                    self.field_name_case.apply(field.name()),
+                ));
+            } else {
+                decode.push(format!("{this} = {decoded}"));
+            }
+
+            if is_closed_enum {
+                decode.dedent();
+
+                if self.preserve_unknown_fields {
+                    decode.push("else");
+                    decode.indent();
+                    decode.push(format!(
+                        "unknownFields = proto.collectUnknownVarInt(unknownFields, {}, value)",
+                        field.number()
+                    ));
+                    decode.dedent();
+                }
+
+                decode.push("end");
+            }
         }
+
+        decode
+    }
+
+    pub fn decode_packed(&self, output: &str, field: &FieldDescriptorProto) -> String {
+        let entry_decode = self.decode(output, field).build();
+
+        indoc::formatdoc! {"
+            local length
+            length, cursor = proto.readVarInt(input, cursor)
+
+            local limit = cursor + length
+
+            while cursor < limit do
+                {entry_decode}
+            end
+        "}
     }
+}
+
+pub fn is_deprecated(field: &FieldDescriptorProto) -> bool {
+    options_deprecated(field.options.as_ref())
+}
+
+/// The descriptor `*Options` messages, which all have a `deprecated` option.
+pub trait DeprecatableOptions {
+    fn is_deprecated(&self) -> bool;
+}
+
+macro_rules! deprecatable_options {
+    ($($options:ty),*) => {
+        $(impl DeprecatableOptions for $options {
+            fn is_deprecated(&self) -> bool {
+                self.deprecated()
+            }
+        })*
+    };
+}
 
-    decode
+deprecatable_options!(
+    EnumOptions,
+    EnumValueOptions,
+    FieldOptions,
+    MessageOptions,
+    MethodOptions,
+    ServiceOptions
+);
+
+pub fn options_deprecated<T: DeprecatableOptions>(options: Option<&T>) -> bool {
+    options.is_some_and(T::is_deprecated)
 }
 
-pub fn is_packed(field_descriptor: &FieldDescriptorProto) -> bool {
+pub fn is_packable(field_descriptor: &FieldDescriptorProto) -> bool {
     if field_descriptor.label.is_none() || field_descriptor.label() != Label::Repeated {
         return false;
     }
 
-    if !matches!(
+    matches!(
         field_descriptor.r#type(),
         Type::Double
             | Type::Float
@@ -1046,41 +1892,189 @@ pub fn is_packed(field_descriptor: &FieldDescriptorProto) -> bool {
             | Type::Sfixed32
             | Type::Sfixed64
             | Type::Bool
-    ) {
-        return false;
+            | Type::Enum
+    )
+}
+
+/// Whether values of the field can't be compared with `==`.
+fn needs_deep_equals(field: &FieldDescriptorProto) -> bool {
+    matches!(
+        field.r#type(),
+        Type::Message | Type::Group | Type::Bytes | Type::Enum
+    )
+}
+
+/// Whether values of the field are mutable, and so have to be copied rather than shared.
+fn needs_deep_clone(field: &FieldDescriptorProto) -> bool {
+    matches!(field.r#type(), Type::Message | Type::Group | Type::Bytes)
+}
+
+fn clone_instruction_field_descriptor_ignore_repeated(
+    field: &FieldDescriptorProto,
+    value_var: &str,
+) -> String {
+    match field.r#type() {
+        Type::Message | Type::Group => format!("{value_var}:clone()"),
+        Type::Bytes => format!("buffer.fromstring(buffer.tostring({value_var}))"),
+        _ => value_var.to_owned(),
+    }
+}
+
+fn enum_type_of_field_descriptor<'a>(
+    field: &FieldDescriptorProto,
+    export_map: &'a ExportMap,
+) -> Option<&'a EnumType> {
+    let type_name = field.type_name();
+
+    export_map
+        .get(type_name.strip_prefix('.').unwrap_or(type_name))
+        .and_then(|export| export.enum_type.as_ref())
+}
+
+// proto2: `[default = ...]`, for the field descriptor. Enums are given by name.
+fn custom_default_of_field_descriptor(
+    field: &FieldDescriptorProto,
+    int64_type: Int64Type,
+) -> Option<String> {
+    let default = field.default_value.as_deref()?;
+
+    if int64_kind(field).is_some() {
+        match int64_type {
+            Int64Type::Number => {}
+            Int64Type::String => return Some(format!("\"{default}\"")),
+            Int64Type::Int64 => {
+                return Some(format!(
+                    "proto.Int64.fromString(\"{default}\", {})",
+                    is_unsigned_64(field)
+                ));
+            }
+        }
+    }
+
+    Some(match field.r#type() {
+        Type::Float | Type::Double => match default {
+            "inf" => "math.huge".to_owned(),
+            "-inf" => "-math.huge".to_owned(),
+            "nan" => "0 / 0".to_owned(),
+            _ => default.to_owned(),
+        },
+
+        Type::Int32
+        | Type::Uint32
+        | Type::Int64
+        | Type::Uint64
+        | Type::Fixed32
+        | Type::Fixed64
+        | Type::Sint32
+        | Type::Sint64
+        | Type::Sfixed32
+        | Type::Sfixed64
+        | Type::Bool => default.to_owned(),
+
+        // Strings are given to us unescaped.
+        Type::String => luau_string_literal(default.as_bytes()),
+
+        // Bytes are given to us C escaped.
+        Type::Bytes => format!(
+            "buffer.fromstring({})",
+            luau_string_literal(&unescape_c_bytes(default))
+        ),
+
+        Type::Enum => luau_string_literal(default.as_bytes()),
+
+        Type::Message | Type::Group => return None,
+    })
+}
+
+pub fn luau_string_literal(bytes: &[u8]) -> String {
+    let mut literal = String::from("\"");
+
+    for &byte in bytes {
+        match byte {
+            b'"' => literal.push_str("\\\""),
+            b'\\' => literal.push_str("\\\\"),
+            b' '..=b'~' => literal.push(byte as char),
+            _ => literal.push_str(&format!("\\x{byte:02X}")),
+        }
     }
 
-    match field_descriptor.options {
-        // proto2: packed is not default
-        Some(ref options) => options.packed != Some(false),
-        None => true,
+    literal.push('"');
+    literal
+}
+
+pub fn luau_float_literal(value: f64) -> String {
+    if value.is_nan() {
+        "0 / 0".to_owned()
+    } else if value.is_infinite() {
+        if value > 0.0 {
+            "math.huge"
+        } else {
+            "-math.huge"
+        }
+        .to_owned()
+    } else {
+        format!("{value:?}")
     }
 }
 
-pub fn decode_packed(
-    field_descriptor: &FieldDescriptorProto,
-    output: &str,
-    field_name_case: FieldNameCase,
-) -> String {
-    let entry_decode = decode_field(
-        output,
-        field_descriptor,
-        &HashMap::new(),
-        &FileDescriptorProto::default(),
-        None,
-        false,
-        field_name_case,
-    )
-    .build();
+const LUAU_KEYWORDS: &[&str] = &[
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "if", "in", "local",
+    "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
 
-    indoc::formatdoc! {"
-        local length
-        length, cursor = proto.readVarInt(input, cursor)
+/// A table key for a proto name, which can be a Luau keyword, such as buf.validate's own `in`.
+pub fn luau_key(name: &str) -> String {
+    if LUAU_KEYWORDS.contains(&name) {
+        format!("[\"{name}\"]")
+    } else {
+        name.to_owned()
+    }
+}
+
+fn unescape_c_bytes(escaped: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut input = escaped.bytes().peekable();
+
+    while let Some(byte) = input.next() {
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
 
-        local limit = cursor + length
+        match input.next() {
+            Some(b'n') => bytes.push(b'\n'),
+            Some(b'r') => bytes.push(b'\r'),
+            Some(b't') => bytes.push(b'\t'),
+            Some(b'a') => bytes.push(0x07),
+            Some(b'b') => bytes.push(0x08),
+            Some(b'f') => bytes.push(0x0C),
+            Some(b'v') => bytes.push(0x0B),
+            Some(b'x') => {
+                let mut value = 0u8;
+                while let Some(digit) = input.peek().and_then(|&digit| (digit as char).to_digit(16))
+                {
+                    value = value.wrapping_mul(16).wrapping_add(digit as u8);
+                    input.next();
+                }
+                bytes.push(value);
+            }
+            Some(digit @ b'0'..=b'7') => {
+                let mut value = digit - b'0';
+                for _ in 0..2 {
+                    match input.peek() {
+                        Some(&next @ b'0'..=b'7') => {
+                            value = value.wrapping_mul(8).wrapping_add(next - b'0');
+                            input.next();
+                        }
+                        _ => break,
+                    }
+                }
+                bytes.push(value);
+            }
+            Some(other) => bytes.push(other),
+            None => bytes.push(b'\\'),
+        }
+    }
 
-        while cursor < limit do
-            {entry_decode}
-        end
-    "}
+    bytes
 }
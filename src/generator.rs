@@ -3,30 +3,48 @@ use std::sync::Arc;
 
 use prost_types::{
     DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
-    compiler::{
-        CodeGeneratorRequest, CodeGeneratorResponse,
-        code_generator_response::{Feature, File},
-    },
+    ServiceDescriptorProto,
+    compiler::{CodeGeneratorRequest, CodeGeneratorResponse, code_generator_response::File},
+    field_descriptor_proto::{Label, Type},
 };
 use rayon::prelude::*;
 use typed_path::{PathType, TypedPath, UnixPath as Path, UnixPathBuf as PathBuf};
 
 use crate::{
+    comments::{
+        Comments, ENUM_VALUE, FILE_ENUM_TYPE, FILE_MESSAGE_TYPE, FILE_SERVICE, MESSAGE_ENUM_TYPE,
+        MESSAGE_FIELD, MESSAGE_NESTED_TYPE, MESSAGE_ONEOF_DECL, SERVICE_METHOD,
+    },
+    editions::{
+        EditionsRequest, EnumKind, FeatureMap, MAXIMUM_EDITION, MINIMUM_EDITION, MessageEncoding,
+        SUPPORTED_FEATURES, edition_name,
+    },
+    embedded_descriptors,
     fields::{
-        FieldGenerator, FieldKind, FieldNameCase, WireType, decode_field, decode_packed, is_packed,
+        FieldGenerator, FieldKind, FieldNameCase, Int64Type, WireType, is_packable,
+        options_deprecated, runtime_definition_of_type_name, type_definition_of_type_name,
         wire_type_of_field_descriptor,
     },
     if_builder::IfBuilder,
+    options::{OptionsMap, OptionsRequest},
     string_builder::StringBuilder,
+    validate_rules,
     wkt_json::WktJson,
+    wkt_methods::WktMethods,
 };
 
 // This is used for options in proto3, but is syntax = proto2.
 // Don't import it, and error if we see it.
 const DESCRIPTORS_IMPORT: &str = "google/protobuf/descriptor.proto";
 
-pub fn generate_response(request: CodeGeneratorRequest) -> CodeGeneratorResponse {
-    let export_map = Arc::new(create_export_map(&request.proto_file));
+pub fn generate_response(
+    request: CodeGeneratorRequest,
+    editions_request: &EditionsRequest,
+    options_request: &OptionsRequest,
+) -> CodeGeneratorResponse {
+    let feature_map = Arc::new(FeatureMap::new(&request.proto_file, editions_request));
+    let options_map = Arc::new(OptionsMap::new(&request.proto_file, options_request));
+    let export_map = Arc::new(create_export_map(&request.proto_file, &feature_map));
 
     let mut files = Vec::new();
 
@@ -43,6 +61,41 @@ pub fn generate_response(request: CodeGeneratorRequest) -> CodeGeneratorResponse
         .unwrap_or_default();
 
     let roblox_imports = options.get("roblox_imports").map(|x| x.as_str()) == Some("true");
+    let roblox_rpc = options.get("roblox_rpc").map(|x| x.as_str()) == Some("true");
+    let roblox_rpc_fire_and_forget = options
+        .get("roblox_rpc_fire_and_forget")
+        .map(|x| x.as_str())
+        == Some("true");
+    let preserve_unknown_fields =
+        options.get("preserve_unknown_fields").map(|x| x.as_str()) == Some("true");
+    let generate_imports = options.get("generate_imports").map(|x| x.as_str()) == Some("true");
+    let embed_descriptors = options.get("embed_descriptors").map(|x| x.as_str()) == Some("true");
+    let omit_deprecated_partial_fields = options
+        .get("omit_deprecated_partial_fields")
+        .map(|x| x.as_str())
+        == Some("true");
+
+    // Go style `M<proto path>=<require path>`, for dependencies generated somewhere else.
+    // Paths ending in `/` map every file under them.
+    let import_mappings: Arc<Vec<(String, String)>> = Arc::new(
+        options
+            .iter()
+            .filter_map(|(key, value)| {
+                key.strip_prefix('M')
+                    .filter(|proto_path| {
+                        proto_path.ends_with(".proto") || proto_path.ends_with('/')
+                    })
+                    .map(|proto_path| (proto_path.to_owned(), value.clone()))
+            })
+            .collect(),
+    );
+    // Where to require the runtime from instead of a `proto` folder beside the output, so that
+    // several outputs can share its registries.
+    let runtime_require = options.get("runtime_require").cloned();
+    let roblox_rpc_folder = options
+        .get("roblox_rpc_folder")
+        .cloned()
+        .unwrap_or_else(|| "ProtoRpc".to_owned());
 
     let field_name_case = match options.get("field_name_case").map(|s| s.as_str()) {
         None => FieldNameCase::Preserve,
@@ -53,7 +106,22 @@ pub fn generate_response(request: CodeGeneratorRequest) -> CodeGeneratorResponse
                 error: Some(format!(
                     "invalid field_name_case: \"{invalid}\" (expected \"snake\" or \"camel\", or omit for default)",
                 )),
-                supported_features: Some(Feature::Proto3Optional as u64),
+                supported_features: Some(SUPPORTED_FEATURES),
+                file: Vec::new(),
+            };
+        }
+    };
+
+    let int64_type = match options.get("int64_type").map(|s| s.as_str()) {
+        None | Some("number") => Int64Type::Number,
+        Some("string") => Int64Type::String,
+        Some("int64") => Int64Type::Int64,
+        Some(invalid) => {
+            return CodeGeneratorResponse {
+                error: Some(format!(
+                    "invalid int64_type: \"{invalid}\" (expected \"number\", \"string\", or \"int64\")",
+                )),
+                supported_features: Some(SUPPORTED_FEATURES),
                 file: Vec::new(),
             };
         }
@@ -63,11 +131,36 @@ pub fn generate_response(request: CodeGeneratorRequest) -> CodeGeneratorResponse
     if roblox_imports {
         proto_init = proto_init
             .replace("require(\"@self/base64\")", "require(script.base64)")
+            .replace(
+                "require(\"@self/decodeError\")",
+                "require(script.decodeError)",
+            )
+            .replace(
+                "require(\"@self/deprecation\")",
+                "require(script.deprecation)",
+            )
+            .replace(
+                "require(\"@self/descriptorPool\")",
+                "require(script.descriptorPool)",
+            )
+            .replace(
+                "require(\"@self/extensionRegistry\")",
+                "require(script.extensionRegistry)",
+            )
+            .replace("require(\"@self/fieldMask\")", "require(script.fieldMask)")
+            .replace("require(\"@self/int64\")", "require(script.int64)")
             .replace("require(\"@self/message\")", "require(script.message)")
+            .replace("require(\"@self/reflect\")", "require(script.reflect)")
+            .replace("require(\"@self/service\")", "require(script.service)")
+            .replace(
+                "require(\"@self/textFormat\")",
+                "require(script.textFormat)",
+            )
             .replace(
                 "require(\"@self/typeRegistry\")",
                 "require(script.typeRegistry)",
-            );
+            )
+            .replace("require(\"@self/validate\")", "require(script.validate)");
     }
 
     let mut type_registry_init = include_str!("./luau/proto/typeRegistry.luau").to_owned();
@@ -81,6 +174,111 @@ pub fn generate_response(request: CodeGeneratorRequest) -> CodeGeneratorResponse
         ..Default::default()
     });
 
+    let mut descriptor_pool_init = include_str!("./luau/proto/descriptorPool.luau").to_owned();
+    if roblox_imports {
+        descriptor_pool_init = descriptor_pool_init
+            .replace("require(\"./message\")", "require(script.Parent.message)")
+            .replace(
+                "require(\"./typeRegistry\")",
+                "require(script.Parent.typeRegistry)",
+            );
+    }
+    files.push(File {
+        name: Some("proto/descriptorPool.luau".to_owned()),
+        content: Some(descriptor_pool_init),
+        ..Default::default()
+    });
+
+    let mut reflect_init = include_str!("./luau/proto/reflect.luau").to_owned();
+    if roblox_imports {
+        reflect_init = reflect_init
+            .replace(
+                "require(\"./deprecation\")",
+                "require(script.Parent.deprecation)",
+            )
+            .replace("require(\"./message\")", "require(script.Parent.message)");
+    }
+    files.push(File {
+        name: Some("proto/reflect.luau".to_owned()),
+        content: Some(reflect_init),
+        ..Default::default()
+    });
+
+    let mut text_format_init = include_str!("./luau/proto/textFormat.luau").to_owned();
+    if roblox_imports {
+        text_format_init = text_format_init
+            .replace("require(\"./message\")", "require(script.Parent.message)")
+            .replace(
+                "require(\"./typeRegistry\")",
+                "require(script.Parent.typeRegistry)",
+            );
+    }
+    files.push(File {
+        name: Some("proto/textFormat.luau".to_owned()),
+        content: Some(text_format_init),
+        ..Default::default()
+    });
+
+    let mut field_mask_init = include_str!("./luau/proto/fieldMask.luau").to_owned();
+    if roblox_imports {
+        field_mask_init = field_mask_init
+            .replace("require(\"./message\")", "require(script.Parent.message)")
+            .replace("require(\"./reflect\")", "require(script.Parent.reflect)")
+            .replace(
+                "require(\"./typeRegistry\")",
+                "require(script.Parent.typeRegistry)",
+            );
+    }
+    files.push(File {
+        name: Some("proto/fieldMask.luau".to_owned()),
+        content: Some(field_mask_init),
+        ..Default::default()
+    });
+
+    let mut decode_error_init = include_str!("./luau/proto/decodeError.luau").to_owned();
+    if roblox_imports {
+        decode_error_init = decode_error_init
+            .replace("require(\"./message\")", "require(script.Parent.message)")
+            .replace(
+                "require(\"./typeRegistry\")",
+                "require(script.Parent.typeRegistry)",
+            );
+    }
+    files.push(File {
+        name: Some("proto/decodeError.luau".to_owned()),
+        content: Some(decode_error_init),
+        ..Default::default()
+    });
+
+    let mut validate_init = include_str!("./luau/proto/validate.luau").to_owned();
+    if roblox_imports {
+        validate_init = validate_init
+            .replace("require(\"./message\")", "require(script.Parent.message)")
+            .replace("require(\"./reflect\")", "require(script.Parent.reflect)")
+            .replace("require(\"./regex\")", "require(script.Parent.regex)")
+            .replace(
+                "require(\"./typeRegistry\")",
+                "require(script.Parent.typeRegistry)",
+            );
+    }
+    files.push(File {
+        name: Some("proto/validate.luau".to_owned()),
+        content: Some(validate_init),
+        ..Default::default()
+    });
+
+    files.push(File {
+        name: Some("proto/deprecation.luau".to_owned()),
+        content: Some(include_str!("./luau/proto/deprecation.luau").to_owned()),
+        ..Default::default()
+    });
+
+    files.push(File {
+        name: Some("proto/regex.luau".to_owned()),
+        content: Some(include_str!("./luau/proto/regex.luau").to_owned()),
+        ..Default::default()
+    });
+
     files.push(File {
         name: Some("proto/init.luau".to_owned()),
         content: Some(proto_init),
@@ -93,17 +291,56 @@ pub fn generate_response(request: CodeGeneratorRequest) -> CodeGeneratorResponse
         ..Default::default()
     });
 
+    let mut extension_registry_init =
+        include_str!("./luau/proto/extensionRegistry.luau").to_owned();
+    if roblox_imports {
+        extension_registry_init = extension_registry_init
+            .replace("require(\"./message\")", "require(script.Parent.message)");
+    }
+    files.push(File {
+        name: Some("proto/extensionRegistry.luau".to_owned()),
+        content: Some(extension_registry_init),
+        ..Default::default()
+    });
+
+    files.push(File {
+        name: Some("proto/int64.luau".to_owned()),
+        content: Some(include_str!("./luau/proto/int64.luau").to_owned()),
+        ..Default::default()
+    });
+
+    files.push(File {
+        name: Some("proto/service.luau".to_owned()),
+        content: Some(include_str!("./luau/proto/service.luau").to_owned()),
+        ..Default::default()
+    });
+
+    if roblox_rpc {
+        files.push(File {
+            name: Some("proto/robloxRpc.luau".to_owned()),
+            content: Some(include_str!("./luau/proto/robloxRpc.luau").to_owned()),
+            ..Default::default()
+        });
+    }
+
     files.push(File {
         name: Some("proto/base64.luau".to_owned()),
         content: Some(include_str!("./luau/proto/base64.luau").to_owned()),
         ..Default::default()
     });
 
+    // The runtime is required from somewhere else, so it isn't generated again here.
+    if runtime_require.is_some() {
+        files.clear();
+    }
+
     // If we import the descriptor proto file, we need to explicitly block
     // everything it tries to import.
     // That way you can use descriptors for options, without needing to parse proto2.
     let mut forbidden_types = HashSet::new();
 
+    let file_to_generate: HashSet<String> = request.file_to_generate.into_iter().collect();
+
     let proto_files_to_process: Vec<_> = request
         .proto_file
         .into_iter()
@@ -128,7 +365,7 @@ pub fn generate_response(request: CodeGeneratorRequest) -> CodeGeneratorResponse
                 }
                 false
             } else {
-                true
+                generate_imports || file_to_generate.contains(file.name())
             }
         })
         .collect();
@@ -138,17 +375,75 @@ pub fn generate_response(request: CodeGeneratorRequest) -> CodeGeneratorResponse
     let results: Vec<_> = proto_files_to_process
         .into_par_iter()
         .map(|file| {
-            if file.syntax() != "proto3" {
-                return Err(format!("{} is not proto3", file.name()));
+            match file.syntax() {
+                "" | "proto2" | "proto3" => {}
+
+                "editions" => {
+                    let edition = feature_map.edition(file.name());
+                    if !(MINIMUM_EDITION..=MAXIMUM_EDITION).contains(&edition) {
+                        return Err(format!(
+                            "{} uses unsupported edition {}",
+                            file.name(),
+                            edition_name(edition)
+                        ));
+                    }
+                }
+
+                syntax => {
+                    return Err(format!(
+                        "{} uses unsupported syntax \"{syntax}\"",
+                        file.name()
+                    ));
+                }
             }
 
-            let mut generator =
-                FileGenerator::new(file, &export_map, &forbidden_types, field_name_case);
+            let embedded_descriptor = embed_descriptors.then(|| {
+                embedded_descriptors::base64_encode(&embedded_descriptors::encode_file(
+                    &file,
+                    &feature_map,
+                ))
+            });
+
+            let mut generator = FileGenerator::new(
+                file,
+                &export_map,
+                &feature_map,
+                &options_map,
+                &forbidden_types,
+                field_name_case,
+                int64_type,
+            );
 
             if roblox_imports {
                 generator.enable_roblox_imports();
             }
 
+            if roblox_rpc {
+                generator.enable_roblox_rpc(roblox_rpc_folder.clone());
+            }
+
+            if roblox_rpc_fire_and_forget {
+                generator.enable_roblox_rpc_fire_and_forget();
+            }
+
+            if preserve_unknown_fields {
+                generator.enable_preserve_unknown_fields();
+            }
+
+            if omit_deprecated_partial_fields {
+                generator.enable_omit_deprecated_partial_fields();
+            }
+
+            if let Some(embedded_descriptor) = embedded_descriptor {
+                generator.enable_embed_descriptors(embedded_descriptor);
+            }
+
+            generator.set_import_mappings(&import_mappings);
+
+            if let Some(runtime_require) = &runtime_require {
+                generator.set_runtime_require(runtime_require.clone());
+            }
+
             let generated = generator.generate_file();
             Ok((generated.file, generated.errors))
         })
@@ -177,7 +472,7 @@ pub fn generate_response(request: CodeGeneratorRequest) -> CodeGeneratorResponse
         } else {
             Some(errors.join("\n"))
         },
-        supported_features: Some(Feature::Proto3Optional as u64),
+        supported_features: Some(SUPPORTED_FEATURES),
         file: files,
     }
 }
@@ -187,6 +482,7 @@ pub struct Export {
     pub path: PathBuf,
     pub prefix: String,
     pub map: Option<MapType>,
+    pub enum_type: Option<EnumType>,
 }
 
 #[derive(Debug)]
@@ -195,9 +491,16 @@ pub struct MapType {
     pub value: FieldDescriptorProto,
 }
 
+#[derive(Debug)]
+pub struct EnumType {
+    pub first_value: String,
+    // Closed enums drop unknown values instead of keeping the number.
+    pub closed: bool,
+}
+
 pub type ExportMap = HashMap<String, Export>;
 
-fn create_export_map(files: &[FileDescriptorProto]) -> ExportMap {
+fn create_export_map(files: &[FileDescriptorProto], feature_map: &FeatureMap) -> ExportMap {
     let mut export_map = HashMap::new();
 
     // todo: all descriptors
@@ -205,11 +508,25 @@ fn create_export_map(files: &[FileDescriptorProto]) -> ExportMap {
         let path = PathBuf::from(file.name()).with_extension("");
 
         for descriptor in &file.enum_type {
-            add_enum_descriptors(descriptor, &mut export_map, file.package(), &path, "");
+            add_enum_descriptors(
+                descriptor,
+                &mut export_map,
+                file.package(),
+                &path,
+                "",
+                feature_map,
+            );
         }
 
         for descriptor in &file.message_type {
-            add_message_descriptors(descriptor, &mut export_map, file.package(), &path, "");
+            add_message_descriptors(
+                descriptor,
+                &mut export_map,
+                file.package(),
+                &path,
+                "",
+                feature_map,
+            );
         }
     }
 
@@ -222,6 +539,7 @@ fn add_message_descriptors(
     package: &str,
     path: &Path,
     prefix: &str,
+    feature_map: &FeatureMap,
 ) {
     if export_map
         .insert(
@@ -230,6 +548,7 @@ fn add_message_descriptors(
                 path: path.to_path_buf(),
                 prefix: prefix.to_owned(),
                 map: extract_map(descriptor),
+                enum_type: None,
             },
         )
         .is_some()
@@ -244,6 +563,7 @@ fn add_message_descriptors(
             &format!("{package}.{}", descriptor.name()),
             path,
             &format!("{prefix}{}_", descriptor.name()),
+            feature_map,
         );
     }
 
@@ -254,6 +574,7 @@ fn add_message_descriptors(
             &format!("{package}.{}", descriptor.name()),
             path,
             &format!("{prefix}{}_", descriptor.name()),
+            feature_map,
         );
     }
 }
@@ -278,14 +599,26 @@ fn add_enum_descriptors(
     package: &str,
     path: &Path,
     prefix: &str,
+    feature_map: &FeatureMap,
 ) {
+    let full_name = format!("{package}.{}", descriptor.name());
+    let closed = feature_map.get(&full_name).enum_type == EnumKind::Closed;
+
     if export_map
         .insert(
-            format!("{package}.{}", descriptor.name()),
+            full_name,
             Export {
                 path: path.to_path_buf(),
                 prefix: prefix.to_owned(),
                 map: None,
+                enum_type: Some(EnumType {
+                    first_value: descriptor
+                        .value
+                        .first()
+                        .map(|value| value.name().to_owned())
+                        .unwrap_or_default(),
+                    closed,
+                }),
             },
         )
         .is_some()
@@ -340,7 +673,7 @@ do
     _<name>Impl.__index = _<name>Impl
 
     function _<name>Impl.new(data: _<name>PartialFields?): <name>
-        return setmetatable({
+<report_deprecated>        return setmetatable({
     <default>
         }, _<name>Impl :: _<name>Impl)
     end
@@ -355,14 +688,22 @@ do
         return shrunkBuffer
     end
 
-    function _<name>Impl.decode(input: buffer): <name>
-        local self = _<name>Impl.new()
+    function _<name>Impl.decode(input: buffer, options: proto.DecodeOptions?): <name>
+        return _<name>Impl.decodeInto(_<name>Impl.new(), input, options)
+    end
+
+    function _<name>Impl.decodeInto(self: <name>, input: buffer, options: proto.DecodeOptions?): <name>
+        local limits = proto.decodeLimits(input, options)
         local cursor = 0
+        <unknown_fields_start>
 
         while cursor < buffer.len(input) do
+            <unknown_field_start>
             local field, wireType
             field, wireType, cursor = proto.readTag(input, cursor)
 
+            <decode_extensions>
+
             if wireType == proto.wireTypes.varint then
                 <decode_varint>
 
@@ -385,22 +726,78 @@ do
 
                 local _
                 _, cursor = proto.readFixed64(input, cursor)
+            elseif wireType == proto.wireTypes.sgroup then
+                <decode_sgroup>
+
+                cursor = proto.skipGroup(input, cursor, field, limits)
             else
                 error("Unsupported wire type: " .. wireType)
             end
+
+            <unknown_field_end>
         end
 
+        <unknown_fields_end>
+
+        <required_checks>
         return self
     end
 
+    function _<name>Impl.tryDecode(input: buffer, options: proto.DecodeOptions?): (<name>?, proto.DecodeError?)
+        return proto.tryDecode(_<name>Impl, input, options)
+    end
+
+    function _<name>Impl.tryJsonDecode(input: any): (<name>?, proto.DecodeError?)
+        return proto.tryJsonDecode(_<name>Impl, input)
+    end
+
+    function _<name>Impl.validate(self: <name>): { proto.Violation }
+        return proto.validate(self, <validation_rules>)
+    end
+
+    function _<name>Impl.equals(self: <name>, other: <name>): boolean
+        if rawequal(self, other) then
+            return true
+        end
+
+    <equals>
+        return true
+    end
+
+    function _<name>Impl.clone(self: <name>): <name>
+        local copy = _<name>Impl.new()
+
+    <clone>
+        return copy
+    end
+
+    function _<name>Impl.mergeFrom(self: <name>, other: <name>)
+    <merge_from>
+    end
+
     <json>
 
+    <text_format>
+
+    <extension_methods>
+
+    <default_getters>
+
     _<name>Impl.descriptor = {
-        name = "<name>",
-        fullName = "<full_name>",
+        name = "<raw_name>",
+        fullName = "<descriptor_full_name>",
+        file = "<file>",
+        package = "<package>",
+        fields = {
+    <field_descriptors>
+        },
+        oneofs = { <oneof_descriptors> },
+        nestedTypes = { <nested_types> },
+        enumTypes = { <enum_types> },
+        options = <custom_options>,
     }
 
-    <any_methods>
+    <wkt_methods>
 
     messages.<name> = _<name>Impl :: any  -- Luau: Not sure why this intersection fails.
 
@@ -418,6 +815,16 @@ function _<name>Impl.jsonDecode(input: { [string]: any }): <name>
 end
 "#;
 
+const TEXT_FORMAT: &str = r#"
+function _<name>Impl.toTextFormat(self: <name>): string
+    return proto.textFormat.encode(self)
+end
+
+function _<name>Impl.fromTextFormat(input: string): <name>
+    return proto.textFormat.decode(_<name>Impl, input)
+end
+"#;
+
 const ENUM: &str = r#"messages.<name> = {
     fromNumber = function(value: number): <name>?
         <from_number>
@@ -434,94 +841,462 @@ const ENUM: &str = r#"messages.<name> = {
     variants = {
         <variants>
     },
-}"#;
-
-const ANY_METHOD_SIGNATURES: &str = r#"
--- Pack a message into an Any.
---
--- typeUrlPrefix should be the base URL for the type URL. For example, Google uses
--- "type.googleapis.com".
-pack: (payload: proto.Message<any, any>, typeUrlPrefix: string) -> Any,
-
--- Returns the message contained by the Any (or nil if the Any is empty).
-unpack: (self: Any, registry: typeRegistry.TypeRegistry?) -> proto.Message<any, any>?,
-
--- Returns true if and only if the Any contains an object of the type specified by
--- typeName. If typeName is a full type URL, it will be compared; otherwise,
--- only the type name will be compared.
-isA: (self: Any, typeName: string) -> boolean,
-"#;
 
-fn create_decoder(fields: BTreeMap<i32, String>) -> String {
-    if fields.is_empty() {
-        return "-- No fields".to_owned();
+    descriptor = {
+        name = "<raw_name>",
+        fullName = "<descriptor_full_name>",
+        file = "<file>",
+        package = "<package>",
+        closed = <closed>,
+        values = {
+            <value_descriptors>
+        },
+        options = <custom_options>,
+    },
+}
+
+typeRegistry.default:registerEnum(messages.<name>)"#;
+
+const SERVICE: &str = r#"
+do
+    local _<name>Impl = {}
+
+    function _<name>Impl.newClient(transport: proto.Transport): <name>Client
+        return {
+    <client_methods>
+        }
+    end
+
+    function _<name>Impl.newDispatcher(implementation: <name>Implementation): proto.Transport
+        return function(methodFullName: string, input: buffer): buffer
+            <dispatch>
+
+            error(`Unknown method {methodFullName} for service <full_name>`)
+        end
+    end
+
+    _<name>Impl.descriptor = {
+        name = "<name>",
+        fullName = "<full_name>",
+        methods = {
+    <method_descriptors>
+        },
+        options = <custom_options>,
     }
 
-    let mut lines = StringBuilder::new();
-    lines.indent_n(4);
+    <roblox_rpc>
 
-    for (index, (field, code)) in fields.iter().enumerate() {
-        lines.push(format!(
-            "{} field == {field} then",
-            if index == 0 { "if" } else { "elseif" }
-        ));
-        lines.push(format!("\t{code}"));
-        lines.push("continue");
+    messages.<name> = _<name>Impl<message_cast>
+end
+"#;
+
+const ROBLOX_RPC_SERVICE: &str = r#"
+function _<name>Impl.robloxClient(): <name>RobloxClient
+    return {
+<client_methods>
     }
+end
 
-    lines.push("end");
-    lines.build().trim_start().to_owned()
+function _<name>Impl.robloxServe(implementation: <name>RobloxImplementation): () -> ()
+    local connections: { RBXScriptConnection } = {}
+    local remoteFunctions: { RemoteFunction } = {}
+
+<serve>
+
+    return function()
+        for _, connection in connections do
+            connection:Disconnect()
+        end
+
+        for _, remoteFunction in remoteFunctions do
+            (remoteFunction :: any).OnServerInvoke = nil
+        end
+    end
+end
+"#;
+
+struct ServiceMethod {
+    name: String,
+    input_type: String,
+    output_type: String,
+    input_runtime: String,
+    output_runtime: String,
+    client_streaming: bool,
+    server_streaming: bool,
+    fire_and_forget: bool,
 }
 
-struct FileGenerator<'a> {
-    file_descriptor_proto: FileDescriptorProto,
-    export_map: &'a ExportMap,
+// Unary methods become RemoteFunctions. With roblox_rpc_fire_and_forget, ones returning
+// google.protobuf.Empty are fired and forgotten over a RemoteEvent instead.
+// Server streaming methods become a RemoteEvent the server fires back for every response,
+// tagged with the call it answers, and then once more when the implementation returns.
+// Client streaming has no equivalent, so it's left out.
+fn create_roblox_rpc_service(
+    name: &str,
+    full_name: &str,
+    folder: &str,
+    methods: &[ServiceMethod],
+) -> (StringBuilder, String) {
+    let mut types = StringBuilder::new();
+
+    let mut client_type = StringBuilder::new();
+    client_type.push(format!("export type {name}RobloxClient = {{"));
+    client_type.indent();
+
+    let mut implementation_type = StringBuilder::new();
+    implementation_type.push(format!("export type {name}RobloxImplementation = {{"));
+    implementation_type.indent();
+
+    let mut client_methods = StringBuilder::new();
+    client_methods.indent_n(2);
+
+    let mut serve = StringBuilder::new();
+    serve.indent();
+
+    for method in methods {
+        if method.client_streaming {
+            continue;
+        }
 
-    types: StringBuilder,
-    implementations: StringBuilder,
+        let ServiceMethod {
+            name: method_name,
+            input_type,
+            output_type,
+            input_runtime,
+            output_runtime,
+            ..
+        } = method;
 
-    exports: Vec<String>,
-    names_defined_here: Vec<String>,
-    errors: Vec<String>,
+        let remote_arguments = format!("\"{folder}\", \"{full_name}\", \"{method_name}\"");
 
-    forbidden_types: &'a HashSet<String>,
+        if method.server_streaming {
+            client_type.push(format!(
+                "{method_name}: (self: {name}RobloxClient, request: {input_type}, onResponse: ({output_type}) -> (), onEnd: ((problem: string?) -> ())?) -> RBXScriptConnection,"
+            ));
+            implementation_type.push(format!(
+                "{method_name}: (self: {name}RobloxImplementation, player: Player, request: {input_type}, send: ({output_type}) -> ()) -> (),"
+            ));
 
-    roblox_imports: bool,
-    field_name_case: FieldNameCase,
-}
+            client_methods.push(indoc::formatdoc! {"
+                {method_name} = function(_self: {name}RobloxClient, request: {input_type}, onResponse: ({output_type}) -> (), onEnd: ((problem: string?) -> ())?): RBXScriptConnection
+                    return robloxRpc.callStream(robloxRpc.remoteEvent({remote_arguments}), request:encode(), function(payload: buffer)
+                        onResponse({output_runtime}.decode(payload))
+                    end, onEnd)
+                end,"
+            });
 
-struct FileAndErrors {
-    file: File,
-    errors: Vec<String>,
-}
+            serve.push(indoc::formatdoc! {"
+                table.insert(connections, robloxRpc.serveStream(robloxRpc.remoteEvent({remote_arguments}), function(player: Player, payload: buffer, send: (buffer) -> ())
+                    implementation:{method_name}(player, {input_runtime}.decode(payload), function(response: {output_type})
+                        send(response:encode())
+                    end)
+                end))
+            "});
+        } else if method.fire_and_forget {
+            client_type.push(format!(
+                "{method_name}: (self: {name}RobloxClient, request: {input_type}) -> (),"
+            ));
+            implementation_type.push(format!(
+                "{method_name}: (self: {name}RobloxImplementation, player: Player, request: {input_type}) -> (),"
+            ));
 
-impl<'a> FileGenerator<'a> {
-    fn new(
-        file_descriptor_proto: FileDescriptorProto,
-        export_map: &'a ExportMap,
-        forbidden_types: &'a HashSet<String>,
-        field_name_case: FieldNameCase,
-    ) -> FileGenerator<'a> {
-        Self {
-            file_descriptor_proto,
-            export_map,
+            client_methods.push(indoc::formatdoc! {"
+                {method_name} = function(_self: {name}RobloxClient, request: {input_type})
+                    robloxRpc.remoteEvent({remote_arguments}):FireServer(request:encode())
+                end,"
+            });
 
-            types: StringBuilder::new(),
-            implementations: StringBuilder::new(),
-            names_defined_here: Vec::new(),
-            exports: Vec::new(),
-            errors: Vec::new(),
+            serve.push(indoc::formatdoc! {"
+                do
+                    local remote = robloxRpc.remoteEvent({remote_arguments})
+                    table.insert(connections, remote.OnServerEvent:Connect(function(player: Player, payload: unknown)
+                        implementation:{method_name}(player, {input_runtime}.decode(robloxRpc.expectBuffer(payload)))
+                    end))
+                end
+            "});
+        } else {
+            client_type.push(format!(
+                "{method_name}: (self: {name}RobloxClient, request: {input_type}) -> {output_type},"
+            ));
+            implementation_type.push(format!(
+                "{method_name}: (self: {name}RobloxImplementation, player: Player, request: {input_type}) -> {output_type},"
+            ));
 
-            forbidden_types,
+            client_methods.push(indoc::formatdoc! {"
+                {method_name} = function(_self: {name}RobloxClient, request: {input_type}): {output_type}
+                    local remote = robloxRpc.remoteFunction({remote_arguments})
+                    return {output_runtime}.decode(robloxRpc.expectBuffer(remote:InvokeServer(request:encode())))
+                end,"
+            });
 
-            roblox_imports: false,
-            field_name_case,
+            serve.push(indoc::formatdoc! {"
+                do
+                    local remote = robloxRpc.remoteFunction({remote_arguments})
+                    remote.OnServerInvoke = function(player: Player, payload: unknown): buffer
+                        return implementation:{method_name}(player, {input_runtime}.decode(robloxRpc.expectBuffer(payload))):encode()
+                    end
+                    table.insert(remoteFunctions, remote)
+                end
+            "});
         }
     }
 
-    fn enable_roblox_imports(&mut self) {
-        self.roblox_imports = true;
-    }
+    client_type.dedent();
+    client_type.push("}");
+
+    implementation_type.dedent();
+    implementation_type.push("}");
+
+    types.append(&client_type);
+    types.blank();
+    types.append(&implementation_type);
+
+    let implementation = ROBLOX_RPC_SERVICE
+        .replace("    ", "\t")
+        .replace("<name>", name)
+        .replace("<client_methods>", &client_methods.build())
+        .replace("<serve>", &serve.build());
+
+    (types, implementation)
+}
+
+const EXTENSION_METHOD_SIGNATURES: &str = r#"
+getExtension: <T>(self: <name>, extension: proto.Extension<<name>, T>) -> T?,
+setExtension: <T>(self: <name>, extension: proto.Extension<<name>, T>, value: T?) -> (),
+hasExtension: <T>(self: <name>, extension: proto.Extension<<name>, T>) -> boolean,
+"#;
+
+const EXTENSION_METHODS: &str = r#"
+function _<name>Impl.getExtension(self: <name>, extension: proto.Extension<<name>, any>): any
+    local extensions = self._extensions
+    if extensions == nil then
+        return nil
+    end
+
+    return extensions[extension]
+end
+
+function _<name>Impl.setExtension(self: <name>, extension: proto.Extension<<name>, any>, value: any)
+    assert(extension.extendee == "<full_name>", `{extension.fullName} does not extend <full_name>`)
+
+    local extensions = self._extensions or {}
+    extensions[extension] = value
+    self._extensions = extensions
+end
+
+function _<name>Impl.hasExtension(self: <name>, extension: proto.Extension<<name>, any>): boolean
+    local extensions = self._extensions
+    return extensions ~= nil and extensions[extension] ~= nil
+end
+"#;
+
+const DECODE_EXTENSIONS: &str = r#"local extension = extensionRegistry.default:find("<full_name>", field)
+if extension ~= nil then
+    local extensions = self._extensions or {}
+    extensions[extension], cursor = extension.decode(input, cursor, wireType, extensions[extension], limits)
+    self._extensions = extensions
+    continue
+end"#;
+
+const EXTENSION: &str = r#"
+messages.<name> = {
+    name = "<raw_name>",
+    fullName = "<full_name>",
+    extendee = "<extendee>",
+    number = <number>,
+
+    encode = function(value: <type>, output: buffer, cursor: number): (buffer, number)
+        local self = { <field_name> = value }
+
+    <encode>
+        return output, cursor
+    end,
+
+    decode = function(
+        input: buffer,
+        cursor: number,
+        wireType: number,
+        existing: <type>?,
+        limits: proto.DecodeOptions?
+    ): (<type>, number)
+        local self: any = { <field_name> = <existing> }
+
+        <decode>
+
+        return self.<field_name>, cursor
+    end,
+
+    equals = function(value: <type>, otherValue: <type>): boolean
+        local self, other = { <field_name> = value }, { <field_name> = otherValue }
+
+    <equals>
+        return true
+    end,
+
+    merge = function(existing: <type>?, value: <type>): <type>
+        local self: any = { <field_name> = <existing> }
+        local other = { <field_name> = value }
+
+    <merge_from>
+        return self.<field_name>
+    end,
+}
+
+extensionRegistry.default:register(messages.<name>)
+"#;
+
+fn uses_extensions(file: &FileDescriptorProto) -> bool {
+    fn message_uses_extensions(message: &DescriptorProto) -> bool {
+        !message.extension_range.is_empty()
+            || !message.extension.is_empty()
+            || message.nested_type.iter().any(message_uses_extensions)
+    }
+
+    !file.extension.is_empty() || file.message_type.iter().any(message_uses_extensions)
+}
+
+fn wire_type_luau_name(wire_type: WireType) -> &'static str {
+    match wire_type {
+        WireType::Varint => "varint",
+        WireType::LengthDelimited => "lengthDelimited",
+        WireType::I32 => "i32",
+        WireType::I64 => "i64",
+        WireType::StartGroup => "sgroup",
+    }
+}
+
+fn create_decoder(fields: BTreeMap<i32, String>) -> String {
+    if fields.is_empty() {
+        return "-- No fields".to_owned();
+    }
+
+    let mut lines = StringBuilder::new();
+    lines.indent_n(4);
+
+    for (index, (field, code)) in fields.iter().enumerate() {
+        lines.push(format!(
+            "{} field == {field} then",
+            if index == 0 { "if" } else { "elseif" }
+        ));
+        lines.push(format!("\t{code}"));
+        lines.push("continue");
+    }
+
+    lines.push("end");
+    lines.build().trim_start().to_owned()
+}
+
+struct FileGenerator<'a> {
+    file_descriptor_proto: FileDescriptorProto,
+    export_map: &'a ExportMap,
+    feature_map: &'a FeatureMap,
+    options_map: &'a OptionsMap,
+
+    types: StringBuilder,
+    implementations: StringBuilder,
+
+    exports: Vec<String>,
+    names_defined_here: Vec<String>,
+    errors: Vec<String>,
+
+    forbidden_types: &'a HashSet<String>,
+    comments: Comments,
+
+    roblox_imports: bool,
+    roblox_rpc_folder: Option<String>,
+    roblox_rpc_fire_and_forget: bool,
+    preserve_unknown_fields: bool,
+    omit_deprecated_partial_fields: bool,
+    embedded_descriptor: Option<String>,
+    import_mappings: &'a [(String, String)],
+    runtime_require: Option<String>,
+    field_name_case: FieldNameCase,
+    int64_type: Int64Type,
+}
+
+struct FileAndErrors {
+    file: File,
+    errors: Vec<String>,
+}
+
+impl<'a> FileGenerator<'a> {
+    fn new(
+        file_descriptor_proto: FileDescriptorProto,
+        export_map: &'a ExportMap,
+        feature_map: &'a FeatureMap,
+        options_map: &'a OptionsMap,
+        forbidden_types: &'a HashSet<String>,
+        field_name_case: FieldNameCase,
+        int64_type: Int64Type,
+    ) -> FileGenerator<'a> {
+        // The well-known types' hand written JSON and methods (Duration and Timestamp arithmetic, the
+        // wrappers' JSON) are written against numbers, so they ignore int64_type. Int64Value and
+        // UInt64Value lose precision past 2^53 as a result, which the README calls out.
+        let int64_type = if file_descriptor_proto.package() == "google.protobuf" {
+            Int64Type::Number
+        } else {
+            int64_type
+        };
+
+        Self {
+            comments: Comments::new(file_descriptor_proto.source_code_info.as_ref()),
+            file_descriptor_proto,
+            export_map,
+            feature_map,
+            options_map,
+
+            types: StringBuilder::new(),
+            implementations: StringBuilder::new(),
+            names_defined_here: Vec::new(),
+            exports: Vec::new(),
+            errors: Vec::new(),
+
+            forbidden_types,
+
+            roblox_imports: false,
+            roblox_rpc_folder: None,
+            roblox_rpc_fire_and_forget: false,
+            preserve_unknown_fields: false,
+            omit_deprecated_partial_fields: false,
+            embedded_descriptor: None,
+            import_mappings: &[],
+            runtime_require: None,
+            field_name_case,
+            int64_type,
+        }
+    }
+
+    fn enable_roblox_imports(&mut self) {
+        self.roblox_imports = true;
+    }
+
+    fn enable_roblox_rpc(&mut self, folder: String) {
+        self.roblox_rpc_folder = Some(folder);
+    }
+
+    fn enable_roblox_rpc_fire_and_forget(&mut self) {
+        self.roblox_rpc_fire_and_forget = true;
+    }
+
+    fn enable_preserve_unknown_fields(&mut self) {
+        self.preserve_unknown_fields = true;
+    }
+
+    fn enable_omit_deprecated_partial_fields(&mut self) {
+        self.omit_deprecated_partial_fields = true;
+    }
+
+    fn enable_embed_descriptors(&mut self, serialized: String) {
+        self.embedded_descriptor = Some(serialized);
+    }
+
+    fn set_import_mappings(&mut self, import_mappings: &'a [(String, String)]) {
+        self.import_mappings = import_mappings;
+    }
+
+    fn set_runtime_require(&mut self, runtime_require: String) {
+        self.runtime_require = Some(runtime_require);
+    }
 
     fn generate_file(mut self) -> FileAndErrors {
         let file_path = Path::new(self.file_descriptor_proto.name());
@@ -553,16 +1328,28 @@ impl<'a> FileGenerator<'a> {
         // TODO: Reserve name
         contents.push(format!(
             "local proto = require({})",
-            self.require_path(&proto_require_path)
+            self.runtime_require_path(&proto_require_path, None)
         ));
 
-        let mut type_registry_require_path = proto_require_path.clone();
-        type_registry_require_path.push("typeRegistry");
         contents.push(format!(
             "local typeRegistry = require({})",
-            self.require_path(&type_registry_require_path)
+            self.runtime_require_path(&proto_require_path, Some("typeRegistry"))
         ));
 
+        if uses_extensions(&self.file_descriptor_proto) {
+            contents.push(format!(
+                "local extensionRegistry = require({})",
+                self.runtime_require_path(&proto_require_path, Some("extensionRegistry"))
+            ));
+        }
+
+        if self.roblox_rpc_folder.is_some() && !self.file_descriptor_proto.service.is_empty() {
+            contents.push(format!(
+                "local robloxRpc = require({})",
+                self.runtime_require_path(&proto_require_path, Some("robloxRpc"))
+            ));
+        }
+
         // contents.push("\nlocal messages = {}");
         let line_to_insert_messages = contents.len();
 
@@ -571,6 +1358,14 @@ impl<'a> FileGenerator<'a> {
                 continue;
             }
 
+            if let Some(mapped) = self.mapped_import(import) {
+                contents.push(format!(
+                    "local {} = require({mapped})",
+                    file_path_export_name(Path::new(&import)),
+                ));
+                continue;
+            }
+
             let path_diff = pathdiff::diff_paths(
                 std::path::Path::new(&import),
                 std::path::Path::new(
@@ -618,17 +1413,41 @@ impl<'a> FileGenerator<'a> {
             None => "",
         };
 
-        for message in std::mem::take(&mut self.file_descriptor_proto.message_type) {
-            self.generate_message(&message, "", scope);
+        for (index, message) in std::mem::take(&mut self.file_descriptor_proto.message_type)
+            .iter()
+            .enumerate()
+        {
+            self.generate_message(message, "", scope, &[FILE_MESSAGE_TYPE, index as i32]);
+        }
+
+        for (index, descriptor) in std::mem::take(&mut self.file_descriptor_proto.enum_type)
+            .iter()
+            .enumerate()
+        {
+            self.generate_enum(descriptor, "", scope, &[FILE_ENUM_TYPE, index as i32]);
+        }
+
+        for extension in std::mem::take(&mut self.file_descriptor_proto.extension) {
+            self.generate_extension(&extension, "", scope);
         }
 
-        for descriptor in std::mem::take(&mut self.file_descriptor_proto.enum_type) {
-            self.generate_enum(&descriptor, "");
+        for (index, service) in std::mem::take(&mut self.file_descriptor_proto.service)
+            .iter()
+            .enumerate()
+        {
+            self.generate_service(service, scope, &[FILE_SERVICE, index as i32]);
         }
 
         contents.push(self.types.build());
         contents.push(self.implementations.build());
 
+        if let Some(embedded_descriptor) = &self.embedded_descriptor {
+            contents.push(format!(
+                "proto.descriptorPool.default:addFile(\"{embedded_descriptor}\")"
+            ));
+            contents.blank();
+        }
+
         contents.insert(
             line_to_insert_messages,
             create_messages_init(&self.names_defined_here),
@@ -667,16 +1486,23 @@ impl<'a> FileGenerator<'a> {
         }
     }
 
-    fn generate_message(&mut self, message: &DescriptorProto, prefix: &str, package: &str) {
+    fn generate_message(
+        &mut self,
+        message: &DescriptorProto,
+        prefix: &str,
+        package: &str,
+        path: &[i32],
+    ) {
         let name = format!("{prefix}{}", message.name());
         let full_name = format!("{package}.{}", message.name());
 
-        if !message
+        let is_map_entry = message
             .options
             .as_ref()
             .map(|options| options.map_entry())
-            .unwrap_or(false)
-        {
+            .unwrap_or(false);
+
+        if !is_map_entry {
             self.exports.push(name.clone());
         }
 
@@ -688,29 +1514,20 @@ impl<'a> FileGenerator<'a> {
             None => "{ [string]: any }",
         };
 
-        let is_wkt_any =
-            self.file_descriptor_proto.package() == "google.protobuf" && message.name() == "Any";
+        let wkt_methods = WktMethods::try_create(&self.file_descriptor_proto, message);
+        let wkt_method_signatures = match wkt_methods.as_ref() {
+            Some(wkt_methods) => wkt_methods.signatures,
+            None => "",
+        };
+
+        let is_extendable = !message.extension_range.is_empty();
 
-        let maybe_any_method_signatures = if is_wkt_any {
-            ANY_METHOD_SIGNATURES
+        let maybe_extension_method_signatures = if is_extendable {
+            EXTENSION_METHOD_SIGNATURES.replace("<name>", &name)
         } else {
-            ""
+            String::new()
         };
 
-        self.types.push(format!(
-            r#"type _{name}Impl = {{
-                __index: _{name}Impl,
-                new: (fields: _{name}PartialFields?) -> {name},
-                encode: (self: {name}) -> buffer,
-                decode: (input: buffer) -> {name},
-                jsonEncode: (self: {name}) -> {json_type},
-                jsonDecode: (input: {json_type}) -> {name},
-                descriptor: proto.Descriptor,
-                {maybe_any_method_signatures}
-            }}
-            "#
-        ));
-
         let mut fields_builder = StringBuilder::new();
         let mut partial_fields_builder = StringBuilder::new();
 
@@ -723,6 +1540,11 @@ impl<'a> FileGenerator<'a> {
         let mut default_lines = StringBuilder::new();
         default_lines.indent_n(3);
 
+        let mut default_getter_signatures = StringBuilder::new();
+        let mut default_getters = StringBuilder::new();
+
+        let mut report_deprecated_lines = Vec::new();
+
         let mut encode_lines = StringBuilder::new();
         encode_lines.indent_n(2);
 
@@ -732,13 +1554,45 @@ impl<'a> FileGenerator<'a> {
         let mut json_decode_lines = StringBuilder::new();
         json_decode_lines.indent_n(2);
 
+        let mut required_checks = StringBuilder::new();
+        required_checks.indent_n(2);
+
+        let mut equals_lines = StringBuilder::new();
+        equals_lines.indent_n(2);
+
+        let mut clone_lines = StringBuilder::new();
+        clone_lines.indent_n(2);
+
+        let mut merge_from_lines = StringBuilder::new();
+        merge_from_lines.indent_n(2);
+
         let mut varint_fields: BTreeMap<i32, String> = BTreeMap::new();
         let mut len_fields: BTreeMap<i32, String> = BTreeMap::new();
         let mut i32_fields: BTreeMap<i32, String> = BTreeMap::new();
         let mut i64_fields: BTreeMap<i32, String> = BTreeMap::new();
+        let mut group_fields: BTreeMap<i32, String> = BTreeMap::new();
+
+        // Editions spell groups as delimited messages, so generate them the same way.
+        let mut message_fields = message.field.clone();
+        for field in &mut message_fields {
+            if field.r#type() == Type::Message
+                && self.feature_map.field(&full_name, field).message_encoding
+                    == MessageEncoding::Delimited
+            {
+                field.set_type(Type::Group);
+            }
+        }
+
+        let oneof_names: Vec<&str> = message
+            .oneof_decl
+            .iter()
+            .map(|oneof| oneof.name())
+            .collect();
+
+        let mut field_descriptors: Vec<(i32, String)> = Vec::new();
 
         let mut fields: Vec<FieldGenerator<'_>> = Vec::new();
-        for field in &message.field {
+        for field in &message_fields {
             if let Some(oneof_index) = field.oneof_index
                 && !field.proto3_optional()
             {
@@ -776,8 +1630,12 @@ impl<'a> FileGenerator<'a> {
                         },
 
                         export_map: self.export_map,
+                        feature_map: self.feature_map,
+                        scope: &full_name,
                         base_file: &self.file_descriptor_proto,
                         field_name_case: self.field_name_case,
+                        int64_type: self.int64_type,
+                        preserve_unknown_fields: self.preserve_unknown_fields,
                     });
                 }
             } else {
@@ -793,8 +1651,16 @@ impl<'a> FileGenerator<'a> {
                 fields.push(FieldGenerator {
                     field_kind: FieldKind::Single(field),
                     export_map: self.export_map,
+                    feature_map: self.feature_map,
+                    scope: &full_name,
                     base_file: &self.file_descriptor_proto,
                     field_name_case: self.field_name_case,
+                    int64_type: if is_map_entry && field.number() == 1 {
+                        self.int64_type.for_map_key()
+                    } else {
+                        self.int64_type
+                    },
+                    preserve_unknown_fields: self.preserve_unknown_fields,
                 });
             }
         }
@@ -803,15 +1669,53 @@ impl<'a> FileGenerator<'a> {
         for field in fields {
             let field_name = field.name();
 
-            fields_builder.push(format!("{field_name}: {},", field.type_definition()));
-            partial_fields_builder.push(format!(
-                "{field_name}: {}?,",
-                field.type_definition_no_presence()
-            ));
+            field_descriptors.extend(field.descriptors(&oneof_names, self.options_map, &full_name));
+
+            let field_path = match &field.field_kind {
+                FieldKind::Single(single) => message
+                    .field
+                    .iter()
+                    .position(|field| field.number() == single.number())
+                    .map(|index| [MESSAGE_FIELD, index as i32]),
+                FieldKind::OneOf { name, .. } => message
+                    .oneof_decl
+                    .iter()
+                    .position(|oneof| oneof.name() == name)
+                    .map(|index| [MESSAGE_ONEOF_DECL, index as i32]),
+            }
+            .map(|field_path| [path, &field_path].concat())
+            .unwrap_or_default();
+
+            let deprecated = field.is_deprecated();
+            let omit_from_partial_fields = deprecated && self.omit_deprecated_partial_fields;
+
+            self.comments.push(
+                &mut fields_builder,
+                &field_path,
+                deprecated,
+                format!("{field_name}: {},", field.type_definition()),
+            );
+
+            if !omit_from_partial_fields {
+                self.comments.push(
+                    &mut partial_fields_builder,
+                    &field_path,
+                    deprecated,
+                    format!("{field_name}: {}?,", field.type_definition_no_presence()),
+                );
+            }
 
             encode_lines.append(&field.encode());
             encode_lines.blank();
 
+            equals_lines.append(&field.equals());
+            equals_lines.blank();
+
+            clone_lines.append(&field.deep_clone());
+
+            merge_from_lines.append(&field.merge_from());
+            merge_from_lines.blank();
+
             if wkt_json.is_none() {
                 json_encode_lines.append(&field.json_encode());
                 json_encode_lines.blank();
@@ -819,23 +1723,65 @@ impl<'a> FileGenerator<'a> {
                 json_decode_lines.append(&field.json_decode());
             }
 
+            if field.is_required() {
+                required_checks.push(format!(
+                    "if self.{field_name} == nil then error(\"Missing required field {field_name} of {full_name}\") end"
+                ));
+            }
+
+            // Deprecated fields left out of the partial fields can still be passed in, untyped.
+            let data = if omit_from_partial_fields {
+                "(data :: any)"
+            } else {
+                "data"
+            };
+
             default_lines.push(format!(
-                r#"{field_name} = if data == nil or data.{field_name} == nil then {} else data.{field_name},"#,
-                field.default()
+                r#"{field_name} = if data == nil or {data}.{field_name} == nil then {} else {data}.{field_name},"#,
+                field.initial()
             ));
 
+            // proto2: Fields with a custom default start out unset, so read it through a getter.
+            if field.has_custom_default() {
+                let getter = format!("get{}", heck::AsUpperCamelCase(&field_name));
+                let field_type = field.type_definition_no_presence();
+
+                default_getter_signatures
+                    .push(format!("{getter}: (self: {name}) -> {field_type},"));
+
+                default_getters.push(format!(
+                    "function _{name}Impl.{getter}(self: {name}): {field_type}"
+                ));
+                default_getters.push(format!(
+                    "\treturn if self.{field_name} == nil then {} else self.{field_name}",
+                    field.default()
+                ));
+                default_getters.push("end");
+                default_getters.blank();
+            }
+
             for inner_field in field.inner_fields() {
                 let output = &format!("self.{field_name}");
 
-                let decoded = decode_field(
-                    output,
-                    inner_field,
-                    self.export_map,
-                    &self.file_descriptor_proto,
-                    field.map_type(),
-                    matches!(field.field_kind, FieldKind::OneOf { .. }),
-                    self.field_name_case,
-                );
+                let mut decoded = field.decode(output, inner_field);
+
+                if let Some(report) = field.report_deprecated(inner_field, "set") {
+                    report_deprecated_lines.push(match &field.field_kind {
+                        FieldKind::Single(_) => format!(
+                            "if data ~= nil and {data}.{field_name} ~= nil then {report} end"
+                        ),
+                        FieldKind::OneOf { .. } => format!(
+                            r#"if data ~= nil and data.{field_name} ~= nil and data.{field_name}.type == "{}" then {report} end"#,
+                            field.luau_name(inner_field.name())
+                        ),
+                    });
+                }
+
+                let report_decode = field.report_deprecated(inner_field, "decode");
+
+                if let Some(report) = &report_decode {
+                    decoded.insert(0, report);
+                }
 
                 match wire_type_of_field_descriptor(inner_field) {
                     WireType::Varint => {
@@ -853,17 +1799,140 @@ impl<'a> FileGenerator<'a> {
                     WireType::I64 => {
                         i64_fields.insert(inner_field.number(), decoded.build());
                     }
+
+                    WireType::StartGroup => {
+                        group_fields.insert(inner_field.number(), decoded.build());
+                    }
                 }
 
-                if is_packed(inner_field) {
+                // Parsers have to accept both packed and unpacked input, whatever we encode with.
+                if is_packable(inner_field) {
+                    let decoded_packed = field.decode_packed(output, inner_field);
                     len_fields.insert(
                         inner_field.number(),
-                        decode_packed(inner_field, output, self.field_name_case),
+                        match &report_decode {
+                            Some(report) => format!("{report}\n{decoded_packed}"),
+                            None => decoded_packed,
+                        },
                     );
                 }
             }
         }
 
+        let required_checks = required_checks.build();
+
+        // Oneofs group their fields together, so put them back in declaration order.
+        field_descriptors.sort_by_key(|(number, _)| {
+            message
+                .field
+                .iter()
+                .position(|field| field.number() == *number)
+        });
+
+        let mut field_descriptor_lines = StringBuilder::new();
+        field_descriptor_lines.indent_n(2);
+        for (_, descriptor) in &field_descriptors {
+            field_descriptor_lines.push(format!("{descriptor},"));
+        }
+
+        // Synthetic oneofs, from proto3 optional, aren't real oneofs.
+        let oneof_descriptors = message
+            .oneof_decl
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| {
+                message.field.iter().any(|field| {
+                    field.oneof_index == Some(*index as i32) && !field.proto3_optional()
+                })
+            })
+            .map(|(_, oneof)| {
+                let custom_options = self
+                    .options_map
+                    .custom_options(&format!("{full_name}.{}", oneof.name()))
+                    .map(|custom_options| format!(", options = {custom_options}"))
+                    .unwrap_or_default();
+
+                format!(
+                    r#"{{ name = "{}", luauName = "{}"{custom_options} }},"#,
+                    oneof.name(),
+                    self.field_name_case.apply(oneof.name())
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let descriptor_full_name = full_name.trim_start_matches('.');
+
+        let nested_types = message
+            .nested_type
+            .iter()
+            .map(|nested| format!(r#""{descriptor_full_name}.{}","#, nested.name()))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let enum_types = message
+            .enum_type
+            .iter()
+            .map(|nested| format!(r#""{descriptor_full_name}.{}","#, nested.name()))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if is_extendable {
+            fields_builder.push(format!(
+                "_extensions: {{ [proto.Extension<{name}, any>]: any }}?,"
+            ));
+
+            encode_lines.push("if self._extensions ~= nil then");
+            encode_lines.indent();
+            encode_lines
+                .push("output, cursor = proto.writeExtensions(output, cursor, self._extensions)");
+            encode_lines.dedent();
+            encode_lines.push("end");
+            encode_lines.blank();
+
+            equals_lines.push(
+                "if not proto.extensionsEqual(self._extensions, other._extensions) then return false end",
+            );
+            equals_lines.blank();
+
+            clone_lines.push(
+                "copy._extensions = if self._extensions == nil then nil else proto.mergeExtensions(nil, self._extensions)",
+            );
+
+            merge_from_lines.push(
+                "if other._extensions ~= nil then self._extensions = proto.mergeExtensions(self._extensions, other._extensions) end",
+            );
+        }
+
+        if self.preserve_unknown_fields {
+            fields_builder.push("_unknownFields: buffer?,");
+
+            encode_lines.push("if self._unknownFields ~= nil then");
+            encode_lines.indent();
+            encode_lines.push(
+                "output, cursor = proto.writeUnknownFields(output, cursor, self._unknownFields)",
+            );
+            encode_lines.dedent();
+            encode_lines.push("end");
+            encode_lines.blank();
+
+            equals_lines.push(
+                "if not proto.unknownFieldsEqual(self._unknownFields, other._unknownFields) then return false end",
+            );
+            equals_lines.blank();
+
+            clone_lines.push(
+                "copy._unknownFields = if self._unknownFields == nil then nil else buffer.fromstring(buffer.tostring(self._unknownFields))",
+            );
+
+            merge_from_lines.push(indoc::indoc! {"
+                local unknownFields = other._unknownFields
+                if unknownFields ~= nil then
+                    self._unknownFields = proto.appendUnknownField(self._unknownFields, unknownFields, 0, buffer.len(unknownFields))
+                end
+            "});
+        }
+
         fields_builder.dedent();
         fields_builder.push("}");
         fields_builder.blank();
@@ -872,13 +1941,45 @@ impl<'a> FileGenerator<'a> {
         partial_fields_builder.push("}");
         partial_fields_builder.blank();
 
+        let default_getter_signatures = default_getter_signatures.build();
+        self.types.push(format!(
+            r#"type _{name}Impl = {{
+                __index: _{name}Impl,
+                new: (fields: _{name}PartialFields?) -> {name},
+                encode: (self: {name}) -> buffer,
+                decode: (input: buffer, options: proto.DecodeOptions?) -> {name},
+                decodeInto: (self: {name}, input: buffer, options: proto.DecodeOptions?) -> {name},
+                jsonEncode: (self: {name}) -> {json_type},
+                jsonDecode: (input: {json_type}) -> {name},
+                tryDecode: (input: buffer, options: proto.DecodeOptions?) -> ({name}?, proto.DecodeError?),
+                tryJsonDecode: (input: {json_type}) -> ({name}?, proto.DecodeError?),
+                validate: (self: {name}) -> {{ proto.Violation }},
+                toTextFormat: (self: {name}) -> string,
+                fromTextFormat: (input: string) -> {name},
+                equals: (self: {name}, other: {name}) -> boolean,
+                clone: (self: {name}) -> {name},
+                mergeFrom: (self: {name}, other: {name}) -> (),
+                descriptor: proto.Descriptor,
+                {wkt_method_signatures}
+                {maybe_extension_method_signatures}
+                {default_getter_signatures}
+            }}
+            "#
+        ));
+
         self.types.append(&fields_builder);
         self.types.blank();
         self.types.append(&partial_fields_builder);
 
-        self.types.push(format!(
-            "export type {name} = typeof(setmetatable({{}} :: _{name}Fields, {{}} :: _{name}Impl))"
-        ));
+        let deprecated = options_deprecated(message.options.as_ref());
+        self.comments.push(
+            &mut self.types,
+            path,
+            deprecated,
+            format!(
+                "export type {name} = typeof(setmetatable({{}} :: _{name}Fields, {{}} :: _{name}Impl))"
+            ),
+        );
 
         self.types.push(format!(
             "type _{name}Message = proto.Message<{name}, _{name}PartialFields> {}",
@@ -891,16 +1992,88 @@ impl<'a> FileGenerator<'a> {
 
         self.types.blank();
 
+        self.errors
+            .extend(validate_rules::unsupported_validation_rules(
+                self.options_map,
+                message,
+                &full_name,
+            ));
+
         let mut final_code = MESSAGE
             .replace("    ", "\t")
             .replace("<name>", &name)
             .replace("<full_name>", &full_name)
+            .replace("<raw_name>", message.name())
+            .replace("<descriptor_full_name>", descriptor_full_name)
+            .replace("<file>", self.file_descriptor_proto.name())
+            .replace("<package>", self.file_descriptor_proto.package())
+            .replace("<field_descriptors>", &field_descriptor_lines.build())
+            .replace("<oneof_descriptors>", &oneof_descriptors)
+            .replace("<nested_types>", &nested_types)
+            .replace("<enum_types>", &enum_types)
+            .replace(
+                "<custom_options>",
+                self.options_map.custom_options(&full_name).unwrap_or("{}"),
+            )
             .replace("<default>", &default_lines.build())
+            .replace("<default_getters>", &default_getters.build())
+            .replace(
+                "<report_deprecated>",
+                &report_deprecated_lines
+                    .iter()
+                    .map(|line| format!("{line}\n"))
+                    .collect::<String>(),
+            )
+            .replace(
+                "<decode_extensions>",
+                if is_extendable { DECODE_EXTENSIONS } else { "" },
+            )
+            .replace(
+                "<unknown_fields_start>",
+                if self.preserve_unknown_fields {
+                    "local unknownFields: proto.UnknownFieldRanges? = nil"
+                } else {
+                    ""
+                },
+            )
+            .replace(
+                "<unknown_field_start>",
+                if self.preserve_unknown_fields {
+                    "local fieldStart = cursor"
+                } else {
+                    ""
+                },
+            )
+            .replace(
+                "<unknown_field_end>",
+                if self.preserve_unknown_fields {
+                    "unknownFields = proto.collectUnknownField(unknownFields, input, fieldStart, cursor)"
+                } else {
+                    ""
+                },
+            )
+            .replace(
+                "<unknown_fields_end>",
+                if self.preserve_unknown_fields {
+                    "if unknownFields ~= nil then self._unknownFields = proto.appendUnknownFields(self._unknownFields, unknownFields) end"
+                } else {
+                    ""
+                },
+            )
+            .replace("<required_checks>", required_checks.trim_start())
+            .replace(
+                "<validation_rules>",
+                &validate_rules::message_validation_rules(self.options_map, message, &full_name),
+            )
             .replace("<encode>", &encode_lines.build())
+            .replace("<equals>", &equals_lines.build())
+            .replace("<clone>", &clone_lines.build())
+            .replace("<merge_from>", &merge_from_lines.build())
             .replace("<decode_varint>", &create_decoder(varint_fields))
             .replace("<decode_len>", &create_decoder(len_fields))
             .replace("<decode_i32>", &create_decoder(i32_fields))
-            .replace("<decode_i64>", &create_decoder(i64_fields));
+            .replace("<decode_i64>", &create_decoder(i64_fields))
+            .replace("<decode_sgroup>", &create_decoder(group_fields));
 
         if let Some(wkt_json) = WktJson::try_create(&self.file_descriptor_proto, message) {
             final_code = final_code.replace("<json>", &wkt_json.code);
@@ -919,36 +2092,186 @@ impl<'a> FileGenerator<'a> {
                     .replace(
                         "<json_decode>",
                         &format!(
-                            "local self = _{name}Impl.new()\n\n{}\nreturn self",
-                            json_decode_lines.build()
+                            "local self = _{name}Impl.new()\n\n{}\n{required_checks}\nreturn self",
+                            json_decode_lines.build(),
                         ),
                     ),
             )
         }
 
-        // Add special methods for google.protobuf.Any: pack, unpack, and isA.
-        let any_methods = include_str!("./luau/wkt_mixins/Any_methods.luau");
-        final_code = final_code.replace("<any_methods>", if is_wkt_any { any_methods } else { "" });
+        final_code = final_code.replace(
+            "<text_format>",
+            &TEXT_FORMAT.replace("    ", "\t").replace("<name>", &name),
+        );
+
+        // Add special methods for well-known types, such as pack and unpack on google.protobuf.Any.
+        final_code = final_code.replace(
+            "<wkt_methods>",
+            wkt_methods
+                .as_ref()
+                .map_or("", |wkt_methods| wkt_methods.code),
+        );
+
+        final_code = final_code.replace(
+            "<extension_methods>",
+            &if is_extendable {
+                EXTENSION_METHODS.replace("<name>", &name)
+            } else {
+                String::new()
+            },
+        );
+
+        // DECODE_EXTENSIONS and EXTENSION_METHODS use it too, matching extendees without the leading `.`
+        final_code = final_code.replace("<full_name>", descriptor_full_name);
 
         self.implementations.push(final_code);
         self.implementations.blank();
 
-        for nested_message in &message.nested_type {
-            self.generate_message(nested_message, &format!("{name}_"), package);
+        for (index, nested_message) in message.nested_type.iter().enumerate() {
+            self.generate_message(
+                nested_message,
+                &format!("{name}_"),
+                &full_name,
+                &[path, &[MESSAGE_NESTED_TYPE, index as i32]].concat(),
+            );
         }
 
-        for nested_enum in &message.enum_type {
-            self.generate_enum(nested_enum, &format!("{name}_"));
+        for (index, nested_enum) in message.enum_type.iter().enumerate() {
+            self.generate_enum(
+                nested_enum,
+                &format!("{name}_"),
+                &full_name,
+                &[path, &[MESSAGE_ENUM_TYPE, index as i32]].concat(),
+            );
+        }
+
+        for nested_extension in &message.extension {
+            self.generate_extension(nested_extension, &format!("{name}_"), &full_name);
         }
     }
 
-    fn generate_enum(&mut self, descriptor: &EnumDescriptorProto, prefix: &str) {
+    fn generate_extension(&mut self, field: &FieldDescriptorProto, prefix: &str, scope: &str) {
+        // Extensions of descriptor.proto are custom options, which only matter to protoc.
+        if self.forbidden_types.contains(field.extendee()) {
+            return;
+        }
+
+        // Files without a package give a leading `.` scope, which descriptors leave out.
+        let full_name = format!("{scope}.{}", field.name())
+            .trim_start_matches('.')
+            .to_owned();
+
+        if self.forbidden_types.contains(field.type_name()) {
+            self.errors.push(format!("{full_name} is not supported"));
+            return;
+        }
+
+        let name = format!("{prefix}{}", field.name());
+
+        let field_generator = FieldGenerator {
+            field_kind: FieldKind::Single(field),
+            export_map: self.export_map,
+            feature_map: self.feature_map,
+            scope,
+            base_file: &self.file_descriptor_proto,
+            field_name_case: self.field_name_case,
+            int64_type: self.int64_type,
+            // Extensions are decoded apart from their message, so they can't keep unknown fields.
+            preserve_unknown_fields: false,
+        };
+
+        let field_name = field_generator.name();
+        let value_type = field_generator.type_definition_no_presence();
+        let extendee_type = type_definition_of_type_name(
+            field.extendee(),
+            self.export_map,
+            &self.file_descriptor_proto,
+        );
+
+        let this = format!("self.{field_name}");
+
+        let mut decode = IfBuilder::new();
+        decode.add_condition(
+            &format!(
+                "wireType == proto.wireTypes.{}",
+                wire_type_luau_name(wire_type_of_field_descriptor(field))
+            ),
+            |builder| builder.append(&field_generator.decode(&this, field)),
+        );
+
+        if is_packable(field) {
+            decode.add_condition("wireType == proto.wireTypes.lengthDelimited", |builder| {
+                builder.push(field_generator.decode_packed(&this, field))
+            });
+        }
+
+        let decode = decode
+            .with_else(|builder| {
+                builder.push(format!(
+                    "error(`Unexpected wire type {{wireType}} for extension {full_name}`)"
+                ))
+            })
+            .build();
+
+        let extendee = field.extendee().trim_start_matches('.');
+
+        self.exports.push(name.clone());
+        self.names_defined_here.push(name.clone());
+
+        self.types.push(format!(
+            "type _{name}Message = proto.Extension<{extendee_type}, {value_type}>"
+        ));
+
+        self.implementations.push(
+            EXTENSION
+                .replace("    ", "\t")
+                .replace("<name>", &name)
+                .replace("<raw_name>", field.name())
+                .replace("<full_name>", &full_name)
+                .replace("<extendee>", extendee)
+                .replace("<number>", &field.number().to_string())
+                .replace("<type>", &value_type)
+                .replace("<field_name>", &field_name)
+                .replace(
+                    "<existing>",
+                    if field.label() == Label::Repeated {
+                        "existing or {}"
+                    } else {
+                        "existing"
+                    },
+                )
+                .replace("<encode>", &field_generator.encode().build())
+                .replace("<decode>", &decode)
+                .replace("<equals>", &field_generator.equals().build())
+                .replace("<merge_from>", &field_generator.merge_from().build()),
+        );
+        self.implementations.blank();
+    }
+
+    fn generate_enum(
+        &mut self,
+        descriptor: &EnumDescriptorProto,
+        prefix: &str,
+        package: &str,
+        path: &[i32],
+    ) {
         let name = format!("{prefix}{}", descriptor.name());
+        let closed = self
+            .feature_map
+            .get(&format!("{package}.{}", descriptor.name()))
+            .enum_type
+            == EnumKind::Closed;
 
         self.types.push(format!(
             "type _{name}Message = proto.Enum<{name}, _{name}Variants>"
         ));
-        self.types.push(format!("export type {name} ="));
+        let deprecated = options_deprecated(descriptor.options.as_ref());
+        self.comments.push(
+            &mut self.types,
+            path,
+            deprecated,
+            format!("export type {name} ="),
+        );
         self.types.indent();
 
         let mut from_number = IfBuilder::new();
@@ -965,6 +2288,9 @@ impl<'a> FileGenerator<'a> {
         let mut variants_type = StringBuilder::new();
         let mut variants_value = StringBuilder::new();
 
+        let mut value_descriptors = StringBuilder::new();
+        value_descriptors.indent_n(3);
+
         for (index, field) in descriptor.value.iter().enumerate() {
             self.types.push(format!(
                 "{}\"{}\"",
@@ -986,14 +2312,34 @@ impl<'a> FileGenerator<'a> {
                 builder.push(format!("return \"{}\"", field.name()));
             });
 
-            variants_type.push(format!(r#"["{name}"]: "{name}","#, name = field.name()));
+            self.comments.push(
+                &mut variants_type,
+                &[path, &[ENUM_VALUE, index as i32]].concat(),
+                options_deprecated(field.options.as_ref()),
+                format!(r#"["{name}"]: "{name}","#, name = field.name()),
+            );
             variants_value.push(format!(
                 r#"{name} = "{name}" :: "{name}","#,
                 name = field.name()
             ));
+
+            let custom_options = self
+                .options_map
+                .custom_options(&format!("{package}.{}.{}", descriptor.name(), field.name()))
+                .map(|custom_options| format!(", options = {custom_options}"))
+                .unwrap_or_default();
+
+            value_descriptors.push(format!(
+                r#"{{ name = "{}", number = {}{custom_options} }},"#,
+                field.name(),
+                field.number()
+            ));
         }
 
-        self.types.push("| number -- Unknown");
+        // Closed enums never hold unknown values.
+        if !closed {
+            self.types.push("| number -- Unknown");
+        }
 
         self.types.dedent();
 
@@ -1021,7 +2367,11 @@ impl<'a> FileGenerator<'a> {
                     "<to_number>",
                     to_number
                         .with_else(|builder| {
-                            builder.push("return self");
+                            if closed {
+                                builder.push(format!("error(`Unknown {name} variant: {{self}}`)"));
+                            } else {
+                                builder.push("return self");
+                            }
                         })
                         .build()
                         .trim_start(),
@@ -1035,11 +2385,290 @@ impl<'a> FileGenerator<'a> {
                         .build()
                         .trim_start(),
                 )
-                .replace("<variants>", &variants_value.build()),
+                .replace("<variants>", &variants_value.build())
+                .replace("<raw_name>", descriptor.name())
+                .replace(
+                    "<descriptor_full_name>",
+                    format!("{package}.{}", descriptor.name()).trim_start_matches('.'),
+                )
+                .replace("<file>", self.file_descriptor_proto.name())
+                .replace("<package>", self.file_descriptor_proto.package())
+                .replace("<closed>", &closed.to_string())
+                .replace(
+                    "<custom_options>",
+                    self.options_map
+                        .custom_options(&format!("{package}.{}", descriptor.name()))
+                        .unwrap_or("{}"),
+                )
+                .replace(
+                    "<value_descriptors>",
+                    value_descriptors.build().trim_start(),
+                ),
         );
         self.implementations.blank();
     }
 
+    fn generate_service(&mut self, service: &ServiceDescriptorProto, package: &str, path: &[i32]) {
+        let name = service.name().to_owned();
+        let full_name = if package.is_empty() {
+            name.clone()
+        } else {
+            format!("{package}.{name}")
+        };
+
+        let deprecated = options_deprecated(service.options.as_ref());
+
+        let mut client_type = StringBuilder::new();
+        self.comments.push(
+            &mut client_type,
+            path,
+            deprecated,
+            format!("export type {name}Client = {{"),
+        );
+        client_type.indent();
+
+        let mut implementation_type = StringBuilder::new();
+        self.comments.push(
+            &mut implementation_type,
+            path,
+            deprecated,
+            format!("export type {name}Implementation = {{"),
+        );
+        implementation_type.indent();
+
+        let mut client_methods = StringBuilder::new();
+        client_methods.indent_n(3);
+
+        let mut dispatch = IfBuilder::new();
+        dispatch.indent_n(3);
+
+        let mut method_descriptors = StringBuilder::new();
+        method_descriptors.indent_n(3);
+
+        let mut methods = Vec::new();
+
+        for (index, method) in service.method.iter().enumerate() {
+            let method_name = method.name();
+            let method_full_name = format!("{full_name}.{method_name}");
+
+            if self.forbidden_types.contains(method.input_type())
+                || self.forbidden_types.contains(method.output_type())
+            {
+                self.errors
+                    .push(format!("{name}::{method_name} is not supported"));
+                continue;
+            }
+
+            let custom_options = self
+                .options_map
+                .custom_options(&format!("{package}.{name}.{method_name}"))
+                .map(|custom_options| format!(", options = {custom_options}"))
+                .unwrap_or_default();
+
+            method_descriptors.push(format!(
+                r#"{{ name = "{method_name}", fullName = "{method_full_name}", inputType = "{}", outputType = "{}", clientStreaming = {}, serverStreaming = {}{custom_options} }},"#,
+                method.input_type().trim_start_matches('.'),
+                method.output_type().trim_start_matches('.'),
+                method.client_streaming(),
+                method.server_streaming(),
+            ));
+
+            let input_type = type_definition_of_type_name(
+                method.input_type(),
+                self.export_map,
+                &self.file_descriptor_proto,
+            );
+            let output_type = type_definition_of_type_name(
+                method.output_type(),
+                self.export_map,
+                &self.file_descriptor_proto,
+            );
+
+            let input_runtime = runtime_definition_of_type_name(
+                method.input_type(),
+                self.export_map,
+                &self.file_descriptor_proto,
+            );
+            let output_runtime = runtime_definition_of_type_name(
+                method.output_type(),
+                self.export_map,
+                &self.file_descriptor_proto,
+            );
+
+            methods.push(ServiceMethod {
+                name: method_name.to_owned(),
+                input_type: input_type.clone(),
+                output_type: output_type.clone(),
+                input_runtime: input_runtime.clone(),
+                output_runtime: output_runtime.clone(),
+                client_streaming: method.client_streaming(),
+                server_streaming: method.server_streaming(),
+                fire_and_forget: self.roblox_rpc_fire_and_forget
+                    && method.output_type() == ".google.protobuf.Empty",
+            });
+
+            // Streaming doesn't fit a single request -> response transport,
+            // so those methods only appear in the descriptor.
+            if method.client_streaming() || method.server_streaming() {
+                continue;
+            }
+
+            let method_path = [path, &[SERVICE_METHOD, index as i32]].concat();
+            let deprecated = options_deprecated(method.options.as_ref());
+            self.comments.push(
+                &mut client_type,
+                &method_path,
+                deprecated,
+                format!(
+                    "{method_name}: (self: {name}Client, request: {input_type}) -> {output_type},"
+                ),
+            );
+            self.comments.push(
+                &mut implementation_type,
+                &method_path,
+                deprecated,
+                format!(
+                    "{method_name}: (self: {name}Implementation, request: {input_type}) -> {output_type},"
+                ),
+            );
+
+            client_methods.push(format!(
+                "{method_name} = function(_self: {name}Client, request: {input_type}): {output_type}"
+            ));
+            client_methods.push(format!(
+                "\treturn {output_runtime}.decode(transport(\"{method_full_name}\", request:encode()))"
+            ));
+            client_methods.push("end,");
+
+            dispatch.add_condition(
+                &format!("methodFullName == \"{method_full_name}\""),
+                |builder| {
+                    builder.push(format!(
+                        "return implementation:{method_name}({input_runtime}.decode(input)):encode()"
+                    ));
+                },
+            );
+        }
+
+        client_type.dedent();
+        client_type.push("}");
+
+        implementation_type.dedent();
+        implementation_type.push("}");
+
+        self.types.append(&client_type);
+        self.types.blank();
+        self.types.append(&implementation_type);
+        self.types.blank();
+
+        let roblox_rpc = self
+            .roblox_rpc_folder
+            .as_ref()
+            .map(|folder| create_roblox_rpc_service(&name, &full_name, folder, &methods));
+
+        if let Some((roblox_types, _)) = &roblox_rpc {
+            self.types.append(roblox_types);
+            self.types.blank();
+            self.types.push(format!(
+                "type _{name}Message = proto.Service<{name}Client, {name}Implementation> \
+                    & robloxRpc.RobloxService<{name}RobloxClient, {name}RobloxImplementation>"
+            ));
+        } else {
+            self.types.push(format!(
+                "type _{name}Message = proto.Service<{name}Client, {name}Implementation>"
+            ));
+        }
+        self.types.blank();
+
+        self.exports.push(name.clone());
+        self.names_defined_here.push(name.clone());
+
+        self.implementations.push(
+            SERVICE
+                .replace("    ", "\t")
+                .replace(
+                    "<roblox_rpc>",
+                    roblox_rpc
+                        .as_ref()
+                        .map(|(_, implementation)| implementation.as_str())
+                        .unwrap_or(""),
+                )
+                .replace(
+                    "<message_cast>",
+                    // Luau: Same intersection failure as messages.
+                    if roblox_rpc.is_some() { " :: any" } else { "" },
+                )
+                .replace("<name>", &name)
+                .replace("<full_name>", &full_name)
+                .replace("<client_methods>", &client_methods.build())
+                .replace("<dispatch>", dispatch.build().trim_start())
+                .replace("<method_descriptors>", &method_descriptors.build())
+                .replace(
+                    "<custom_options>",
+                    self.options_map
+                        .custom_options(&format!("{package}.{name}"))
+                        .unwrap_or("{}"),
+                ),
+        );
+        self.implementations.blank();
+    }
+
+    /// The require for an import given with `M<proto path>=<require path>`, if there is one.
+    /// With `roblox_imports`, the require path is an instance path and used as is.
+    fn mapped_import(&self, import: &str) -> Option<String> {
+        // An exact mapping wins over a directory, and a deeper directory over a shallower one,
+        // whatever order the options came in.
+        let exact = self
+            .import_mappings
+            .iter()
+            .find(|(proto_path, _)| proto_path == import)
+            .map(|(_, require_path)| require_path.clone());
+
+        let require_path = match exact {
+            Some(require_path) => require_path,
+            None => {
+                let (proto_path, require_path) = self
+                    .import_mappings
+                    .iter()
+                    .filter(|(proto_path, _)| {
+                        proto_path.ends_with('/') && import.starts_with(proto_path.as_str())
+                    })
+                    .max_by_key(|(proto_path, _)| proto_path.len())?;
+
+                let rest = &import[proto_path.len()..];
+                let rest = rest.strip_suffix(".proto").unwrap_or(rest);
+                if self.roblox_imports {
+                    format!("{require_path}.{}", rest.replace('/', "."))
+                } else {
+                    format!("{}/{rest}", require_path.trim_end_matches('/'))
+                }
+            }
+        };
+
+        Some(if self.roblox_imports {
+            require_path
+        } else {
+            format!("\"{require_path}\"")
+        })
+    }
+
+    /// The require for the runtime, or one of its modules, from `runtime_require` if it was given.
+    /// Like an import mapping, it's an instance path used as is with `roblox_imports`.
+    fn runtime_require_path(&self, proto_require_path: &Path, module: Option<&str>) -> String {
+        match (&self.runtime_require, module) {
+            (Some(runtime_require), None) if self.roblox_imports => runtime_require.clone(),
+            (Some(runtime_require), None) => format!("\"{runtime_require}\""),
+            (Some(runtime_require), Some(module)) if self.roblox_imports => {
+                format!("{runtime_require}.{module}")
+            }
+            (Some(runtime_require), Some(module)) => {
+                format!("\"{}/{module}\"", runtime_require.trim_end_matches('/'))
+            }
+            (None, None) => self.require_path(proto_require_path),
+            (None, Some(module)) => self.require_path(&proto_require_path.join(module)),
+        }
+    }
+
     fn require_path(&self, path: &Path) -> String {
         use typed_path::UnixComponent as Component;
 
@@ -34,6 +34,10 @@ impl IfBuilder {
         self
     }
 
+    pub fn is_empty(&self) -> bool {
+        !self.has_conditions
+    }
+
     pub fn build(self) -> String {
         self.into_string_builder().build()
     }
@@ -13,30 +13,44 @@ async fn create_samples_once() {
 
 fn generate_samples() {
     let files = [
+        "custom_options.proto",
+        "deprecated.proto",
         "descriptors.proto",
         "enum_regression.proto",
+        "extensions.proto",
         "field_case_test.proto",
         "forwards_compatibility.proto",
+        "groups.proto",
+        "int64.proto",
         "kitchen_sink.proto",
         "many_messages.proto",
+        "no_package_extensions.proto",
+        "proto2.proto",
         "recursive.proto",
+        "services.proto",
+        "validate.proto",
         "wkt.proto",
     ];
 
-    let file_descriptor_set = protox::Compiler::new(["./src/samples/protos"])
-        .unwrap()
+    let mut compiler = protox::Compiler::new(["./src/samples/protos"]).unwrap();
+    compiler
         .include_imports(true)
+        .include_source_info(true)
         .open_files(files)
-        .unwrap()
-        .file_descriptor_set();
+        .unwrap();
 
-    let response =
-        crate::generator::generate_response(prost_types::compiler::CodeGeneratorRequest {
+    let file_descriptor_set = compiler.file_descriptor_set();
+
+    let response = crate::generator::generate_response(
+        prost_types::compiler::CodeGeneratorRequest {
             file_to_generate: files.iter().map(|&string| string.to_owned()).collect(),
-            parameter: None,
+            parameter: Some("generate_imports=true".to_owned()),
             proto_file: file_descriptor_set.file,
             compiler_version: None,
-        });
+        },
+        &Default::default(),
+        &options_request(&compiler),
+    );
 
     assert_eq!(response.error, None);
 
@@ -52,7 +66,7 @@ fn generate_samples() {
         std::fs::write(path, proto_file.content()).unwrap();
     }
 
-    // Generate additional samples with field_name_case options
+    // Generate additional samples with options
     generate_sample_with_parameter(
         "field_case_test.proto",
         "field_case_test_snake",
@@ -63,6 +77,59 @@ fn generate_samples() {
         "field_case_test_camel",
         "field_name_case=camel",
     );
+
+    generate_sample_with_parameter(
+        "forwards_compatibility.proto",
+        "forwards_compatibility_preserve",
+        "preserve_unknown_fields=true",
+    );
+    generate_sample_with_parameter(
+        "proto2.proto",
+        "proto2_preserve",
+        "preserve_unknown_fields=true",
+    );
+
+    generate_sample_with_parameter("int64.proto", "int64_string", "int64_type=string");
+    generate_sample_with_parameter("int64.proto", "int64_int64", "int64_type=int64");
+
+    generate_sample_with_parameter(
+        "services.proto",
+        "roblox_rpc",
+        "roblox_rpc=true,generate_imports=true",
+    );
+
+    generate_sample_with_parameter(
+        "deprecated.proto",
+        "deprecated_omitted",
+        "omit_deprecated_partial_fields=true",
+    );
+
+    generate_sample_with_parameter(
+        "kitchen_sink.proto",
+        "embed_descriptors",
+        "embed_descriptors=true,generate_imports=true",
+    );
+    generate_sample_with_parameter(
+        "proto2.proto",
+        "embed_descriptors_proto2",
+        "embed_descriptors=true",
+    );
+}
+
+/// The custom options of everything the compiler opened, which `file_descriptor_set` drops.
+fn options_request(compiler: &protox::Compiler) -> crate::options::OptionsRequest {
+    #[derive(prost::Message)]
+    struct OptionsFileSet {
+        #[prost(message, repeated, tag = "1")]
+        file: Vec<crate::options::OptionsFile>,
+    }
+
+    let file_set: OptionsFileSet =
+        prost::Message::decode(compiler.encode_file_descriptor_set().as_slice()).unwrap();
+
+    crate::options::OptionsRequest {
+        proto_file: file_set.file,
+    }
 }
 
 /// Compiles the given proto with the given generator parameter and writes output to `samples/{output_dir}/`.
@@ -74,13 +141,16 @@ fn generate_sample_with_parameter(proto_file: &str, output_dir: &str, parameter:
         .unwrap()
         .file_descriptor_set();
 
-    let response =
-        crate::generator::generate_response(prost_types::compiler::CodeGeneratorRequest {
+    let response = crate::generator::generate_response(
+        prost_types::compiler::CodeGeneratorRequest {
             file_to_generate: vec![proto_file.to_owned()],
             parameter: Some(parameter.to_owned()),
             proto_file: file_descriptor_set.file,
             compiler_version: None,
-        });
+        },
+        &Default::default(),
+        &Default::default(),
+    );
 
     assert!(
         response.error.is_none(),
@@ -122,6 +192,16 @@ async fn basic() {
     run_luau_test(Path::new("basic.luau")).await;
 }
 
+#[tokio::test]
+async fn roblox_rpc_remotes() {
+    run_luau_test(Path::new("roblox_rpc.luau")).await;
+}
+
+#[tokio::test]
+async fn deprecated() {
+    run_luau_test(Path::new("deprecated.luau")).await;
+}
+
 #[tokio::test]
 async fn descriptors_require() {
     run_luau_test(Path::new("descriptors_require.luau")).await;
@@ -132,6 +212,81 @@ async fn many_messages() {
     run_luau_test(Path::new("many_messages.luau")).await;
 }
 
+#[tokio::test]
+async fn proto2() {
+    run_luau_test(Path::new("proto2.luau")).await;
+}
+
+#[tokio::test]
+async fn field_mask() {
+    run_luau_test(Path::new("field_mask.luau")).await;
+}
+
+#[tokio::test]
+async fn groups() {
+    run_luau_test(Path::new("groups.luau")).await;
+}
+
+#[tokio::test]
+async fn extensions() {
+    run_luau_test(Path::new("extensions.luau")).await;
+}
+
+#[tokio::test]
+async fn decode_limits() {
+    run_luau_test(Path::new("decode_limits.luau")).await;
+}
+
+#[tokio::test]
+async fn custom_options() {
+    run_luau_test(Path::new("custom_options.luau")).await;
+}
+
+#[tokio::test]
+async fn validate() {
+    run_luau_test(Path::new("validate.luau")).await;
+}
+
+#[tokio::test]
+async fn try_decode() {
+    run_luau_test(Path::new("try_decode.luau")).await;
+}
+
+#[tokio::test]
+async fn unknown_fields() {
+    run_luau_test(Path::new("unknown_fields.luau")).await;
+}
+
+#[tokio::test]
+async fn int64() {
+    run_luau_test(Path::new("int64.luau")).await;
+}
+
+#[tokio::test]
+async fn merge() {
+    run_luau_test(Path::new("merge.luau")).await;
+}
+
+#[tokio::test]
+async fn reflect() {
+    run_luau_test(Path::new("reflect.luau")).await;
+}
+
+#[tokio::test]
+async fn embed_descriptors() {
+    run_luau_test(Path::new("embed_descriptors.luau")).await;
+}
+
+#[tokio::test]
+async fn services() {
+    run_luau_test(Path::new("services.luau")).await;
+}
+
+#[tokio::test]
+async fn text_format() {
+    run_luau_test(Path::new("text_format.luau")).await;
+}
+
 #[tokio::test]
 async fn wkt_json() {
     run_luau_test(Path::new("wkt_json.luau")).await;
@@ -142,17 +297,21 @@ fn descriptors_uses_it() {
     let file_descriptor_set = protox::Compiler::new(["./src/samples/protos"])
         .unwrap()
         .include_imports(true)
-        .open_files(vec!["./src/samples/protos/descriptors_uses_it.proto"])
+        .open_files(vec!["descriptors_uses_it.proto"])
         .unwrap()
         .file_descriptor_set();
 
     assert!(
-        crate::generator::generate_response(prost_types::compiler::CodeGeneratorRequest {
-            file_to_generate: vec!["./src/samples/protos/descriptors_uses_it.proto".to_owned()],
-            parameter: None,
-            proto_file: file_descriptor_set.file,
-            compiler_version: None,
-        })
+        crate::generator::generate_response(
+            prost_types::compiler::CodeGeneratorRequest {
+                file_to_generate: vec!["descriptors_uses_it.proto".to_owned()],
+                parameter: None,
+                proto_file: file_descriptor_set.file,
+                compiler_version: None,
+            },
+            &Default::default(),
+            &Default::default(),
+        )
         .error
         .is_some()
     );
@@ -167,13 +326,16 @@ fn field_name_case_invalid_returns_error() {
         .unwrap()
         .file_descriptor_set();
 
-    let response =
-        crate::generator::generate_response(prost_types::compiler::CodeGeneratorRequest {
+    let response = crate::generator::generate_response(
+        prost_types::compiler::CodeGeneratorRequest {
             file_to_generate: vec!["./src/samples/protos/field_case_test.proto".to_owned()],
             parameter: Some("field_name_case=other".to_owned()),
             proto_file: file_descriptor_set.file,
             compiler_version: None,
-        });
+        },
+        &Default::default(),
+        &Default::default(),
+    );
 
     assert!(
         response
@@ -188,6 +350,520 @@ fn field_name_case_invalid_returns_error() {
     );
 }
 
+#[test]
+fn unsupported_validate_rules_return_error() {
+    let mut compiler = protox::Compiler::new(["./src/samples/protos"]).unwrap();
+    compiler
+        .include_imports(true)
+        .open_files(["unsupported_validate.proto"])
+        .unwrap();
+
+    let response = crate::generator::generate_response(
+        prost_types::compiler::CodeGeneratorRequest {
+            file_to_generate: vec!["unsupported_validate.proto".to_owned()],
+            parameter: None,
+            proto_file: compiler.file_descriptor_set().file,
+            compiler_version: None,
+        },
+        &Default::default(),
+        &options_request(&compiler),
+    );
+
+    let error = response
+        .error
+        .expect("error should be present for unsupported buf.validate rules");
+
+    let mut errors: Vec<_> = error.lines().collect();
+    errors.sort_unstable();
+
+    assert_eq!(
+        errors,
+        [
+            "my.unsupported_validate.Contact.aliases: buf.validate rule string.email is not supported",
+            "my.unsupported_validate.Contact.display_name: buf.validate rule string.pattern `^\\p{L}+$` is not supported: \\p isn't supported",
+            "my.unsupported_validate.Contact.email: buf.validate rule string.email is not supported",
+            "my.unsupported_validate.Contact.handle: buf.validate rule string.pattern `(?i)^[a-z_]+$` is not supported: only (?:...) groups are supported",
+            "my.unsupported_validate.Contact.phone: buf.validate rule cel is not supported",
+            "my.unsupported_validate.Contact.token: buf.validate rule bytes.pattern `^\\x{41}+\\z` is not supported: \\x needs two hex digits",
+            "my.unsupported_validate.Contact: buf.validate message rules are not supported",
+        ]
+    );
+}
+
+#[test]
+fn roblox_rpc() {
+    let generate = |parameter: &str| {
+        let file_descriptor_set = protox::Compiler::new(["./src/samples/protos"])
+            .unwrap()
+            .include_imports(true)
+            .open_files(vec!["services.proto"])
+            .unwrap()
+            .file_descriptor_set();
+
+        let response = crate::generator::generate_response(
+            prost_types::compiler::CodeGeneratorRequest {
+                file_to_generate: vec!["services.proto".to_owned()],
+                parameter: Some(parameter.to_owned()),
+                proto_file: file_descriptor_set.file,
+                compiler_version: None,
+            },
+            &Default::default(),
+            &Default::default(),
+        );
+
+        assert_eq!(response.error, None);
+        response
+    };
+
+    let response = generate("roblox_rpc=true,roblox_rpc_folder=Remotes,generate_imports=true");
+
+    assert!(
+        response
+            .file
+            .iter()
+            .any(|file| file.name() == "proto/robloxRpc.luau"),
+        "robloxRpc runtime should be generated"
+    );
+
+    let services_of = |response: &prost_types::compiler::CodeGeneratorResponse| {
+        response
+            .file
+            .iter()
+            .find(|file| file.name() == "services.luau")
+            .expect("services.luau should be generated")
+            .content()
+            .to_owned()
+    };
+
+    let services = services_of(&response);
+
+    assert!(services.contains("local robloxRpc = require(\"./proto/robloxRpc\")"));
+    assert!(
+        services.contains(
+            "robloxRpc.remoteFunction(\"Remotes\", \"my.services.Greeter\", \"SayHello\")"
+        )
+    );
+    assert!(
+        services.contains(
+            "robloxRpc.remoteEvent(\"Remotes\", \"my.services.Greeter\", \"StreamHellos\")"
+        )
+    );
+
+    // Methods returning Empty still wait for the server unless fire and forget is asked for.
+    assert!(
+        services
+            .contains("robloxRpc.remoteFunction(\"Remotes\", \"my.services.Greeter\", \"Notify\")")
+    );
+
+    let fire_and_forget = services_of(&generate(
+        "roblox_rpc=true,roblox_rpc_folder=Remotes,roblox_rpc_fire_and_forget=true",
+    ));
+
+    assert!(
+        fire_and_forget
+            .contains("robloxRpc.remoteEvent(\"Remotes\", \"my.services.Greeter\", \"Notify\")")
+    );
+    assert!(
+        fire_and_forget.contains(
+            "robloxRpc.remoteFunction(\"Remotes\", \"my.services.Greeter\", \"SayHello\")"
+        )
+    );
+
+    // Files without services shouldn't pull in the Roblox-only runtime.
+    let include_me = response
+        .file
+        .iter()
+        .find(|file| file.name() == "include_me.luau")
+        .expect("include_me.luau should be generated")
+        .content();
+
+    assert!(!include_me.contains("robloxRpc"));
+}
+
+#[test]
+fn file_to_generate() {
+    let generate = |parameter: &str| {
+        let file_descriptor_set = protox::Compiler::new(["./src/samples/protos"])
+            .unwrap()
+            .include_imports(true)
+            .open_files(vec!["kitchen_sink.proto"])
+            .unwrap()
+            .file_descriptor_set();
+
+        let response = crate::generator::generate_response(
+            prost_types::compiler::CodeGeneratorRequest {
+                file_to_generate: vec!["kitchen_sink.proto".to_owned()],
+                parameter: Some(parameter.to_owned()),
+                proto_file: file_descriptor_set.file,
+                compiler_version: None,
+            },
+            &Default::default(),
+            &Default::default(),
+        );
+
+        assert_eq!(response.error, None);
+        response.file
+    };
+
+    let file_names = |files: &[prost_types::compiler::code_generator_response::File]| {
+        files
+            .iter()
+            .map(|file| file.name().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    let only_requested = file_names(&generate(""));
+    assert!(only_requested.contains(&"kitchen_sink.luau".to_owned()));
+    assert!(only_requested.contains(&"proto/init.luau".to_owned()));
+    assert!(!only_requested.contains(&"include_me.luau".to_owned()));
+    assert!(!only_requested.contains(&"deeper/include_me2.luau".to_owned()));
+
+    let with_imports = file_names(&generate("generate_imports=true"));
+    assert!(with_imports.contains(&"include_me.luau".to_owned()));
+    assert!(with_imports.contains(&"deeper/include_me2.luau".to_owned()));
+
+    let kitchen_sink = |files: Vec<prost_types::compiler::code_generator_response::File>| {
+        files
+            .into_iter()
+            .find(|file| file.name() == "kitchen_sink.luau")
+            .expect("kitchen_sink.luau should be generated")
+            .content
+            .unwrap()
+    };
+
+    let mapped = kitchen_sink(generate(
+        "Minclude_me.proto=@protos/include_me,Mdeeper/=@protos/nested/",
+    ));
+    assert!(mapped.contains("local _include_me = require(\"@protos/include_me\")"));
+    assert!(mapped.contains("local _deeper_include_me2 = require(\"@protos/nested/include_me2\")"));
+    assert!(mapped.contains("local _same_package = require(\"./same_package\")"));
+
+    let roblox_mapped = kitchen_sink(generate(
+        "roblox_imports=true,Minclude_me.proto=game.ReplicatedStorage.IncludeMe,Mdeeper/=game.ReplicatedStorage.Deeper",
+    ));
+    assert!(
+        roblox_mapped.contains("local _include_me = require(game.ReplicatedStorage.IncludeMe)")
+    );
+    assert!(roblox_mapped.contains(
+        "local _deeper_include_me2 = require(game.ReplicatedStorage.Deeper.include_me2)"
+    ));
+
+    let shared_runtime = generate("runtime_require=@shared/proto");
+    assert!(
+        file_names(&shared_runtime)
+            .iter()
+            .all(|name| !name.starts_with("proto/"))
+    );
+
+    let shared_runtime = kitchen_sink(shared_runtime);
+    assert!(shared_runtime.contains("local proto = require(\"@shared/proto\")"));
+    assert!(
+        shared_runtime.contains("local typeRegistry = require(\"@shared/proto/typeRegistry\")")
+    );
+
+    let roblox_shared_runtime = kitchen_sink(generate(
+        "roblox_imports=true,runtime_require=game.ReplicatedStorage.Shared.proto",
+    ));
+    assert!(roblox_shared_runtime.contains(
+        "local typeRegistry = require(game.ReplicatedStorage.Shared.proto.typeRegistry)"
+    ));
+}
+
+#[test]
+fn import_mappings_precedence() {
+    let file_descriptor_set = protox::Compiler::new(["./src/samples/protos"])
+        .unwrap()
+        .include_imports(true)
+        .open_files(vec!["wkt.proto"])
+        .unwrap()
+        .file_descriptor_set();
+
+    // The options are parsed into a map, so their order can't decide which mapping wins.
+    let response = crate::generator::generate_response(
+        prost_types::compiler::CodeGeneratorRequest {
+            file_to_generate: vec!["wkt.proto".to_owned()],
+            parameter: Some(
+                "Mgoogle/protobuf/=@wkt,Mgoogle/=@google,Mgoogle/protobuf/timestamp.proto=@ts"
+                    .to_owned(),
+            ),
+            proto_file: file_descriptor_set.file,
+            compiler_version: None,
+        },
+        &Default::default(),
+        &Default::default(),
+    );
+
+    assert_eq!(response.error, None);
+
+    let wkt = response
+        .file
+        .into_iter()
+        .find(|file| file.name() == "wkt.luau")
+        .expect("wkt.luau should be generated")
+        .content
+        .unwrap();
+
+    assert!(wkt.contains("local _google_protobuf_timestamp = require(\"@ts\")"));
+    assert!(wkt.contains("local _google_protobuf_duration = require(\"@wkt/duration\")"));
+}
+
+#[test]
+fn comments() {
+    let file_descriptor_set = protox::Compiler::new(["./src/samples/protos"])
+        .unwrap()
+        .include_imports(true)
+        .include_source_info(true)
+        .open_files(vec!["comments.proto"])
+        .unwrap()
+        .file_descriptor_set();
+
+    let response = crate::generator::generate_response(
+        prost_types::compiler::CodeGeneratorRequest {
+            file_to_generate: vec!["comments.proto".to_owned()],
+            parameter: None,
+            proto_file: file_descriptor_set.file,
+            compiler_version: None,
+        },
+        &Default::default(),
+        &Default::default(),
+    );
+
+    assert_eq!(response.error, None);
+
+    let comments = response
+        .file
+        .iter()
+        .find(|file| file.name() == "comments.luau")
+        .expect("comments.luau should be generated")
+        .content();
+
+    assert!(comments.contains(
+        "--[=[\n\tA message with comments on it and its fields.\n\n\tSpans more than one paragraph.\n]=]\nexport type CommentedMessage = "
+    ));
+    assert!(comments.contains("\t--[=[ A plain int32. ]=]\n\tint32: number,"));
+    assert!(comments.contains("\t--[=[ Either an int or a string. ]=]\n\toneofValue: "));
+    assert!(comments.contains("--[=[ An enum with comments. ]=]\nexport type Commented ="));
+    assert!(comments.contains("\t[\"One\"]: \"One\", -- The first non-default value\n"));
+    assert!(comments.contains(
+        "\toptionalInt32: number?,\n\t-- Unlike int32, tells an unset value apart from zero.\n\t-- Trails over two lines.\n"
+    ));
+    assert!(comments.contains("--[=[ Says hello. ]=]\nexport type GreeterClient = {"));
+    assert!(comments.contains(
+        "\t--[=[ Replies with a greeting. ]=]\n\tSayHello: (self: GreeterImplementation"
+    ));
+}
+
+#[test]
+fn editions() {
+    use crate::editions::{
+        EDITION_2023, EditionsEnum, EditionsField, EditionsFile, EditionsMessage, EditionsRequest,
+        EnumOptions, FeatureSet, FieldOptions, SUPPORTED_FEATURES,
+    };
+    use prost_types::{
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FileDescriptorProto,
+        field_descriptor_proto::{Label, Type},
+    };
+
+    fn field(name: &str, number: i32, label: Label, r#type: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            json_name: Some(name.to_owned()),
+            number: Some(number),
+            label: Some(label as i32),
+            r#type: Some(r#type as i32),
+            ..Default::default()
+        }
+    }
+
+    fn with_features(feature_set: FeatureSet) -> EditionsField {
+        EditionsField {
+            options: Some(FieldOptions {
+                features: Some(feature_set),
+            }),
+        }
+    }
+
+    let file = |name: &str| FileDescriptorProto {
+        name: Some(name.to_owned()),
+        package: Some("my.editions".to_owned()),
+        syntax: Some("editions".to_owned()),
+        message_type: vec![DescriptorProto {
+            name: Some("EditionsMessage".to_owned()),
+            field: vec![
+                field("explicit_field", 1, Label::Optional, Type::Int32),
+                field("implicit_field", 2, Label::Optional, Type::Int32),
+                field("required_field", 3, Label::Optional, Type::Int32),
+                field("expanded_field", 4, Label::Repeated, Type::Int32),
+                field("packed_field", 5, Label::Repeated, Type::Int32),
+                FieldDescriptorProto {
+                    type_name: Some(".my.editions.Color".to_owned()),
+                    ..field("color_field", 6, Label::Optional, Type::Enum)
+                },
+                field("unchecked_field", 7, Label::Optional, Type::String),
+                FieldDescriptorProto {
+                    type_name: Some(".my.editions.EditionsMessage".to_owned()),
+                    ..field("delimited_field", 8, Label::Optional, Type::Message)
+                },
+            ],
+            ..Default::default()
+        }],
+        enum_type: vec![EnumDescriptorProto {
+            name: Some("Color".to_owned()),
+            value: vec![
+                EnumValueDescriptorProto {
+                    name: Some("COLOR_UNSPECIFIED".to_owned()),
+                    number: Some(0),
+                    ..Default::default()
+                },
+                EnumValueDescriptorProto {
+                    name: Some("RED".to_owned()),
+                    number: Some(1),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let editions_file = |edition: i32| EditionsFile {
+        edition: Some(edition),
+        message_type: vec![EditionsMessage {
+            field: vec![
+                EditionsField::default(),
+                with_features(FeatureSet {
+                    field_presence: Some(2), // IMPLICIT
+                    ..Default::default()
+                }),
+                with_features(FeatureSet {
+                    field_presence: Some(3), // LEGACY_REQUIRED
+                    ..Default::default()
+                }),
+                with_features(FeatureSet {
+                    repeated_field_encoding: Some(2), // EXPANDED
+                    ..Default::default()
+                }),
+                EditionsField::default(),
+                EditionsField::default(),
+                with_features(FeatureSet {
+                    utf8_validation: Some(3), // NONE
+                    ..Default::default()
+                }),
+                with_features(FeatureSet {
+                    message_encoding: Some(2), // DELIMITED
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        }],
+        enum_type: vec![EditionsEnum {
+            options: Some(EnumOptions {
+                features: Some(FeatureSet {
+                    enum_type: Some(2), // CLOSED
+                    ..Default::default()
+                }),
+            }),
+        }],
+        ..Default::default()
+    };
+
+    let response = crate::generator::generate_response(
+        prost_types::compiler::CodeGeneratorRequest {
+            file_to_generate: vec!["editions.proto".to_owned()],
+            parameter: None,
+            proto_file: vec![file("editions.proto")],
+            compiler_version: None,
+        },
+        &EditionsRequest {
+            proto_file: vec![editions_file(EDITION_2023)],
+        },
+        &Default::default(),
+    );
+
+    assert_eq!(response.error, None);
+    assert_eq!(response.supported_features, Some(SUPPORTED_FEATURES));
+
+    let editions = response
+        .file
+        .iter()
+        .find(|file| file.name() == "editions.luau")
+        .expect("editions.luau should be generated")
+        .content();
+
+    // 2023 defaults to explicit presence, unless the field says otherwise.
+    assert!(editions.contains("explicit_field: number?,"));
+    assert!(editions.contains("implicit_field: number,"));
+
+    assert!(editions.contains("Missing required field required_field"));
+
+    assert!(
+        !editions.contains("proto.writeTag(output, cursor, 4, proto.wireTypes.lengthDelimited)")
+    );
+    assert!(
+        editions.contains("proto.writeTag(output, cursor, 5, proto.wireTypes.lengthDelimited)")
+    );
+
+    assert!(editions.contains("export type Color = \"COLOR_UNSPECIFIED\" | \"RED\"\n"));
+    assert!(editions.contains("local variant = messages.Color.fromNumber(value)"));
+
+    assert!(editions.contains("self.unchecked_field = buffer.tostring(value)"));
+
+    assert!(editions.contains("proto.writeGroup(output, cursor, 8, encoded)"));
+    assert!(editions.contains("value, cursor = proto.readGroup(input, cursor, 8, limits)"));
+
+    // prost-types drops editions, so embed_descriptors writes the resolved features back in.
+    let embedded = crate::embedded_descriptors::encode_file(
+        &file("editions.proto"),
+        &crate::editions::FeatureMap::new(
+            &[file("editions.proto")],
+            &EditionsRequest {
+                proto_file: vec![editions_file(EDITION_2023)],
+            },
+        ),
+    );
+    let embedded = <EditionsFile as prost::Message>::decode(embedded.as_slice()).unwrap();
+    let field_features = |index: usize| {
+        embedded.message_type[0].field[index]
+            .options
+            .as_ref()
+            .and_then(|options| options.features.clone())
+            .unwrap()
+    };
+
+    assert_eq!(embedded.edition, Some(EDITION_2023));
+    assert_eq!(field_features(0).field_presence, Some(1)); // EXPLICIT
+    assert_eq!(field_features(1).field_presence, Some(2)); // IMPLICIT
+    assert_eq!(field_features(3).repeated_field_encoding, Some(2)); // EXPANDED
+    assert_eq!(field_features(7).message_encoding, Some(2)); // DELIMITED
+    assert_eq!(
+        embedded.enum_type[0]
+            .options
+            .as_ref()
+            .and_then(|options| options.features.as_ref())
+            .and_then(|features| features.enum_type),
+        Some(2) // CLOSED
+    );
+
+    let response = crate::generator::generate_response(
+        prost_types::compiler::CodeGeneratorRequest {
+            file_to_generate: vec!["future.proto".to_owned()],
+            parameter: None,
+            proto_file: vec![file("future.proto")],
+            compiler_version: None,
+        },
+        &EditionsRequest {
+            proto_file: vec![editions_file(99999)],
+        },
+        &Default::default(),
+    );
+
+    assert_eq!(
+        response.error.as_deref(),
+        Some("future.proto uses unsupported edition 99999 (unknown)")
+    );
+}
+
 #[tokio::test]
 async fn invalid_utf8() {
     run_luau_test(Path::new("invalid_utf8.luau")).await;
@@ -207,3 +883,8 @@ async fn field_case_snake() {
 async fn field_case_camel() {
     run_luau_test(Path::new("field_case_camel.luau")).await;
 }
+
+#[tokio::test]
+async fn wkt_methods() {
+    run_luau_test(Path::new("wkt_methods.luau")).await;
+}
@@ -4,11 +4,19 @@ use std::io::{Read, Write};
 use color_eyre::eyre::WrapErr;
 use prost::Message;
 
+mod comments;
+mod custom_options;
+mod editions;
+mod embedded_descriptors;
 mod fields;
 mod generator;
 mod if_builder;
+mod options;
+mod regex;
 mod string_builder;
+mod validate_rules;
 mod wkt_json;
+mod wkt_methods;
 
 #[cfg(test)]
 mod luau_tests;
@@ -21,10 +29,24 @@ fn main() -> color_eyre::Result<()> {
         .read_to_end(&mut bytes)
         .wrap_err("couldn't read to end of stdin")?;
 
-    let mut output = Vec::new();
-    generator::generate_response(prost_types::compiler::CodeGeneratorRequest::decode(bytes.as_slice()).wrap_err(
+    let request = prost_types::compiler::CodeGeneratorRequest::decode(bytes.as_slice()).wrap_err(
         "couldn't parse CodeGeneratorRequest, make sure you're using this as a plugin to protoc",
-    )?).encode(&mut output).wrap_err("couldn't encode CodeGeneratorResponse")?;
+    )?;
+
+    let editions_request = editions::EditionsRequest::decode(bytes.as_slice())
+        .wrap_err("couldn't parse editions from CodeGeneratorRequest")?;
+
+    let options_request = options::OptionsRequest::decode(bytes.as_slice())
+        .wrap_err("couldn't parse options from CodeGeneratorRequest")?;
+
+    let mut output = Vec::new();
+    generator::generate_response(request, &editions_request, &options_request)
+        .encode(&mut output)
+        .wrap_err("couldn't encode CodeGeneratorResponse")?;
+
+    editions::EditionsResponse::supported()
+        .encode(&mut output)
+        .wrap_err("couldn't encode CodeGeneratorResponse editions")?;
 
     std::io::stdout()
         .write_all(&output)
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use prost_types::{DescriptorProto, EnumDescriptorProto, FileDescriptorProto};
+
+use crate::custom_options::{
+    CustomOptionTypes, ENUM_OPTIONS, ENUM_VALUE_OPTIONS, FIELD_OPTIONS, MESSAGE_OPTIONS,
+    METHOD_OPTIONS, ONEOF_OPTIONS, SERVICE_OPTIONS,
+};
+
+// prost-types drops extensions while decoding options, so custom options never reach its descriptors.
+// We decode the request a second time with every options message kept as bytes, and line them up
+// with the prost-types descriptors by index, the same way as editions.
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OptionsRequest {
+    #[prost(message, repeated, tag = "15")]
+    pub proto_file: Vec<OptionsFile>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OptionsFile {
+    #[prost(message, repeated, tag = "4")]
+    pub message_type: Vec<OptionsMessage>,
+    #[prost(message, repeated, tag = "5")]
+    pub enum_type: Vec<OptionsEnum>,
+    #[prost(message, repeated, tag = "6")]
+    pub service: Vec<OptionsService>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OptionsMessage {
+    #[prost(message, repeated, tag = "2")]
+    pub field: Vec<OptionsField>,
+    #[prost(message, repeated, tag = "3")]
+    pub nested_type: Vec<OptionsMessage>,
+    #[prost(message, repeated, tag = "4")]
+    pub enum_type: Vec<OptionsEnum>,
+    #[prost(bytes = "vec", optional, tag = "7")]
+    pub options: Option<Vec<u8>>,
+    #[prost(message, repeated, tag = "8")]
+    pub oneof_decl: Vec<OptionsOneof>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OptionsField {
+    #[prost(bytes = "vec", optional, tag = "8")]
+    pub options: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OptionsOneof {
+    #[prost(bytes = "vec", optional, tag = "2")]
+    pub options: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OptionsEnum {
+    #[prost(message, repeated, tag = "2")]
+    pub value: Vec<OptionsEnumValue>,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub options: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OptionsEnumValue {
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub options: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OptionsService {
+    #[prost(message, repeated, tag = "2")]
+    pub method: Vec<OptionsMethod>,
+    #[prost(bytes = "vec", optional, tag = "3")]
+    pub options: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct OptionsMethod {
+    #[prost(bytes = "vec", optional, tag = "4")]
+    pub options: Option<Vec<u8>>,
+}
+
+/// Serialized options of everything in the request, keyed the same way as the feature map:
+/// "package.Message", "package.Message.field", "package.Message.oneof", "package.Enum",
+/// "package.Enum.VALUE", "package.Service", and "package.Service.Method".
+#[derive(Debug, Default)]
+pub struct OptionsMap {
+    options: HashMap<String, Vec<u8>>,
+    custom_options: HashMap<String, String>,
+}
+
+impl OptionsMap {
+    pub fn new(files: &[FileDescriptorProto], options_request: &OptionsRequest) -> Self {
+        let types = CustomOptionTypes::new(files);
+        let mut options_map = OptionsMap::default();
+
+        for (index, file) in files.iter().enumerate() {
+            let Some(options_file) = options_request.proto_file.get(index) else {
+                continue;
+            };
+
+            for (index, descriptor) in file.message_type.iter().enumerate() {
+                options_map.add_message(
+                    &types,
+                    descriptor,
+                    options_file.message_type.get(index),
+                    file.package(),
+                );
+            }
+
+            for (index, descriptor) in file.enum_type.iter().enumerate() {
+                options_map.add_enum(
+                    &types,
+                    descriptor,
+                    options_file.enum_type.get(index),
+                    file.package(),
+                );
+            }
+
+            for (index, service) in file.service.iter().enumerate() {
+                let Some(options_service) = options_file.service.get(index) else {
+                    continue;
+                };
+
+                let full_name = format!("{}.{}", file.package(), service.name());
+                options_map.insert(
+                    &types,
+                    SERVICE_OPTIONS,
+                    full_name.clone(),
+                    options_service.options.as_ref(),
+                );
+
+                for (index, method) in service.method.iter().enumerate() {
+                    options_map.insert(
+                        &types,
+                        METHOD_OPTIONS,
+                        format!("{full_name}.{}", method.name()),
+                        options_service
+                            .method
+                            .get(index)
+                            .and_then(|method| method.options.as_ref()),
+                    );
+                }
+            }
+        }
+
+        options_map
+    }
+
+    fn add_message(
+        &mut self,
+        types: &CustomOptionTypes,
+        descriptor: &DescriptorProto,
+        options_message: Option<&OptionsMessage>,
+        scope: &str,
+    ) {
+        let Some(options_message) = options_message else {
+            return;
+        };
+
+        let full_name = format!("{scope}.{}", descriptor.name());
+        self.insert(
+            types,
+            MESSAGE_OPTIONS,
+            full_name.clone(),
+            options_message.options.as_ref(),
+        );
+
+        for (index, field) in descriptor.field.iter().enumerate() {
+            self.insert(
+                types,
+                FIELD_OPTIONS,
+                format!("{full_name}.{}", field.name()),
+                options_message
+                    .field
+                    .get(index)
+                    .and_then(|field| field.options.as_ref()),
+            );
+        }
+
+        for (index, oneof) in descriptor.oneof_decl.iter().enumerate() {
+            self.insert(
+                types,
+                ONEOF_OPTIONS,
+                format!("{full_name}.{}", oneof.name()),
+                options_message
+                    .oneof_decl
+                    .get(index)
+                    .and_then(|oneof| oneof.options.as_ref()),
+            );
+        }
+
+        for (index, nested_type) in descriptor.nested_type.iter().enumerate() {
+            self.add_message(
+                types,
+                nested_type,
+                options_message.nested_type.get(index),
+                &full_name,
+            );
+        }
+
+        for (index, nested_enum) in descriptor.enum_type.iter().enumerate() {
+            self.add_enum(
+                types,
+                nested_enum,
+                options_message.enum_type.get(index),
+                &full_name,
+            );
+        }
+    }
+
+    fn add_enum(
+        &mut self,
+        types: &CustomOptionTypes,
+        descriptor: &EnumDescriptorProto,
+        options_enum: Option<&OptionsEnum>,
+        scope: &str,
+    ) {
+        let Some(options_enum) = options_enum else {
+            return;
+        };
+
+        let full_name = format!("{scope}.{}", descriptor.name());
+        self.insert(
+            types,
+            ENUM_OPTIONS,
+            full_name.clone(),
+            options_enum.options.as_ref(),
+        );
+
+        for (index, value) in descriptor.value.iter().enumerate() {
+            self.insert(
+                types,
+                ENUM_VALUE_OPTIONS,
+                format!("{full_name}.{}", value.name()),
+                options_enum
+                    .value
+                    .get(index)
+                    .and_then(|value| value.options.as_ref()),
+            );
+        }
+    }
+
+    fn insert(
+        &mut self,
+        types: &CustomOptionTypes,
+        options_type: &str,
+        name: String,
+        options: Option<&Vec<u8>>,
+    ) {
+        let Some(options) = options else {
+            return;
+        };
+
+        if let Some(custom_options) = types.luau_table(options_type, options) {
+            self.custom_options.insert(name.clone(), custom_options);
+        }
+
+        self.options.insert(name, options.clone());
+    }
+
+    /// The serialized options of a message, field, oneof, enum, enum value, service, or method,
+    /// empty if it has none.
+    pub fn get(&self, name: &str) -> &[u8] {
+        self.options
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The custom options of a message, field, oneof, enum, enum value, service, or method,
+    /// as a Luau table keyed by extension name. `None` if it has none.
+    pub fn custom_options(&self, name: &str) -> Option<&str> {
+        self.custom_options.get(name).map(String::as_str)
+    }
+}
@@ -0,0 +1,201 @@
+//! The syntax that proto/regex.luau accepts, so `pattern` rules it can't match fail generation
+//! instead of erroring in `validate` at runtime. Mirrors that parser, reasons included.
+
+// RE2's limit, which proto/regex.luau also enforces.
+const MAX_REPEAT: u32 = 1000;
+
+struct Parser {
+    characters: Vec<char>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.index).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.characters.get(self.index + offset).copied()
+    }
+
+    /// Reads what comes after a backslash, returning whether it's a class such as `\d`.
+    fn parse_escape(&mut self) -> Result<bool, String> {
+        let Some(character) = self.peek() else {
+            return Err("trailing backslash".to_owned());
+        };
+
+        self.index += 1;
+
+        match character {
+            'd' | 'D' | 'w' | 'W' | 's' | 'S' => Ok(true),
+            'n' | 't' | 'r' | 'f' | 'v' => Ok(false),
+            'x' => {
+                let hex = self.characters.iter().skip(self.index).take(2);
+                if hex.filter(|digit| digit.is_ascii_hexdigit()).count() != 2 {
+                    return Err("\\x needs two hex digits".to_owned());
+                }
+
+                self.index += 2;
+                Ok(false)
+            }
+            character if character.is_ascii_alphanumeric() || character == '_' => {
+                Err(format!("\\{character} isn't supported"))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn parse_class(&mut self) -> Result<(), String> {
+        if self.peek() == Some('^') {
+            self.index += 1;
+        }
+
+        let mut first = true;
+
+        loop {
+            let character = match self.peek() {
+                None => return Err("missing ]".to_owned()),
+                Some(']') if !first => {
+                    self.index += 1;
+                    return Ok(());
+                }
+                Some(character) => character,
+            };
+
+            first = false;
+            self.index += 1;
+
+            if character == '\\' && self.parse_escape()? {
+                continue;
+            }
+
+            if self.peek() == Some('-') && self.peek_at(1).is_some_and(|next| next != ']') {
+                self.index += 2;
+
+                if self.characters[self.index - 1] == '\\' && self.parse_escape()? {
+                    return Err("classes can't end a range".to_owned());
+                }
+            }
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<(), String> {
+        let character = self.characters[self.index];
+        self.index += 1;
+
+        match character {
+            '(' => {
+                if self.peek() == Some('?') {
+                    if self.peek_at(1) != Some(':') {
+                        return Err("only (?:...) groups are supported".to_owned());
+                    }
+
+                    self.index += 2;
+                }
+
+                self.parse_alternation()?;
+                if self.peek() != Some(')') {
+                    return Err("missing )".to_owned());
+                }
+
+                self.index += 1;
+                Ok(())
+            }
+            '[' => self.parse_class(),
+            '*' | '+' | '?' => Err(format!("nothing for {character} to repeat")),
+            '\\' => self.parse_escape().map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads `{n}`, `{n,}`, or `{n,m}`, returning `false` if it's a literal `{` instead.
+    fn parse_braces(&mut self) -> Result<bool, String> {
+        // proto/regex.luau only looks this far ahead for the closing brace.
+        let rest: String = self.characters.iter().skip(self.index).take(21).collect();
+
+        let Some(braces) = rest
+            .strip_prefix('{')
+            .and_then(|inner| inner.find('}').map(|end| &inner[..end]))
+        else {
+            return Ok(false);
+        };
+
+        let (min, max) = match braces.split_once(',') {
+            Some((min, max)) => (min, Some(max)),
+            None => (braces, None),
+        };
+
+        let is_number = |text: &str| text.bytes().all(|byte| byte.is_ascii_digit());
+        if min.is_empty() || !is_number(min) || !max.is_none_or(is_number) {
+            return Ok(false);
+        }
+
+        self.index += braces.chars().count() + 2;
+
+        // Long enough runs of digits count as over the limit, rather than overflowing.
+        let count = |text: &str| text.parse::<u32>().unwrap_or(u32::MAX);
+        let minimum = count(min);
+        let maximum = match max {
+            None => Some(minimum),
+            Some("") => None,
+            Some(max) => Some(count(max)),
+        };
+
+        if minimum > MAX_REPEAT || maximum.is_some_and(|maximum| maximum > MAX_REPEAT) {
+            Err(format!("repetition counts can't be over {MAX_REPEAT}"))
+        } else if maximum.is_some_and(|maximum| maximum < minimum) {
+            Err(format!("{{{braces}}} has a maximum under its minimum"))
+        } else {
+            Ok(true)
+        }
+    }
+
+    fn parse_sequence(&mut self) -> Result<(), String> {
+        while self
+            .peek()
+            .is_some_and(|character| character != '|' && character != ')')
+        {
+            self.parse_atom()?;
+
+            loop {
+                match self.peek() {
+                    Some('*' | '+' | '?') => self.index += 1,
+                    Some('{') if self.parse_braces()? => {}
+                    _ => break,
+                }
+
+                if self.peek() == Some('?') {
+                    self.index += 1;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_alternation(&mut self) -> Result<(), String> {
+        self.parse_sequence()?;
+
+        while self.peek() == Some('|') {
+            self.index += 1;
+            self.parse_sequence()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Why proto/regex.luau can't match `pattern`, if it can't. Valid RE2 such as flags, named
+/// groups, `\b`, and `\p{L}` is among what it rejects.
+pub fn unsupported_reason(pattern: &str) -> Option<String> {
+    let mut parser = Parser {
+        characters: pattern.chars().collect(),
+        index: 0,
+    };
+
+    if let Err(reason) = parser.parse_alternation() {
+        return Some(reason);
+    }
+
+    (parser.index < parser.characters.len()).then(|| "unmatched )".to_owned())
+}
@@ -36,9 +36,12 @@ impl StringBuilder {
         }
     }
 
+    // Only strips `other`'s own indentation, since stylua doesn't reindent the insides of block comments.
     pub fn append(&mut self, other: &Self) {
+        let other_indentation = "\t".repeat(other.depth);
+
         for line in &other.lines {
-            self.push(line.trim_start_matches('\t'));
+            self.push(line.strip_prefix(&other_indentation).unwrap_or(line));
         }
     }
 
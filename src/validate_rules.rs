@@ -0,0 +1,767 @@
+use prost::{
+    Message,
+    bytes::Buf,
+    encoding::{DecodeContext, WireType, decode_key, decode_varint, skip_field},
+};
+use prost_types::DescriptorProto;
+
+use crate::{
+    fields::{luau_float_literal, luau_key, luau_string_literal},
+    options::OptionsMap,
+    regex,
+};
+
+// buf.validate rules, decoded from the `(buf.validate.field)` and `(buf.validate.oneof)` options.
+// Only the rules we check are here. Everything else, such as CEL expressions and well-known string
+// formats, is found by `unsupported_validation_rules` from the raw options, and fails generation.
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FieldOptions {
+    #[prost(message, optional, tag = "1159")]
+    field: Option<FieldRules>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct OneofOptions {
+    #[prost(message, optional, tag = "1159")]
+    oneof: Option<OneofRules>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct OneofRules {
+    #[prost(bool, optional, tag = "1")]
+    required: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FieldRules {
+    #[prost(message, optional, tag = "1")]
+    float: Option<FloatRules>,
+    #[prost(message, optional, tag = "2")]
+    double: Option<DoubleRules>,
+    #[prost(message, optional, tag = "3")]
+    int32: Option<Int32Rules>,
+    #[prost(message, optional, tag = "4")]
+    int64: Option<Int64Rules>,
+    #[prost(message, optional, tag = "5")]
+    uint32: Option<UInt32Rules>,
+    #[prost(message, optional, tag = "6")]
+    uint64: Option<UInt64Rules>,
+    #[prost(message, optional, tag = "7")]
+    sint32: Option<SInt32Rules>,
+    #[prost(message, optional, tag = "8")]
+    sint64: Option<SInt64Rules>,
+    #[prost(message, optional, tag = "9")]
+    fixed32: Option<Fixed32Rules>,
+    #[prost(message, optional, tag = "10")]
+    fixed64: Option<Fixed64Rules>,
+    #[prost(message, optional, tag = "11")]
+    sfixed32: Option<SFixed32Rules>,
+    #[prost(message, optional, tag = "12")]
+    sfixed64: Option<SFixed64Rules>,
+    #[prost(message, optional, tag = "13")]
+    bool: Option<BoolRules>,
+    #[prost(message, optional, tag = "14")]
+    string: Option<StringRules>,
+    #[prost(message, optional, tag = "15")]
+    bytes: Option<BytesRules>,
+    #[prost(message, optional, tag = "16")]
+    r#enum: Option<EnumRules>,
+    #[prost(message, optional, tag = "18")]
+    repeated: Option<Box<RepeatedRules>>,
+    #[prost(message, optional, tag = "19")]
+    map: Option<Box<MapRules>>,
+    #[prost(bool, optional, tag = "25")]
+    required: Option<bool>,
+    #[prost(int32, optional, tag = "27")]
+    ignore: Option<i32>,
+}
+
+macro_rules! numeric_rules {
+    ($name:ident, $kind:ident, $rust_type:ty) => {
+        numeric_rules!($name, $kind, $rust_type, {});
+    };
+
+    ($name:ident, $kind:ident, $rust_type:ty, { $($extra:tt)* }) => {
+        #[derive(Clone, PartialEq, prost::Message)]
+        struct $name {
+            #[prost($kind, optional, tag = "1")]
+            r#const: Option<$rust_type>,
+            #[prost($kind, optional, tag = "2")]
+            lt: Option<$rust_type>,
+            #[prost($kind, optional, tag = "3")]
+            lte: Option<$rust_type>,
+            #[prost($kind, optional, tag = "4")]
+            gt: Option<$rust_type>,
+            #[prost($kind, optional, tag = "5")]
+            gte: Option<$rust_type>,
+            #[prost($kind, repeated, packed = "false", tag = "6")]
+            r#in: Vec<$rust_type>,
+            #[prost($kind, repeated, packed = "false", tag = "7")]
+            not_in: Vec<$rust_type>,
+            $($extra)*
+        }
+
+        impl $name {
+            fn luau_table(&self) -> LuauTable {
+                let mut table = LuauTable::default();
+                table.number("const", self.r#const);
+                table.number("lt", self.lt);
+                table.number("lte", self.lte);
+                table.number("gt", self.gt);
+                table.number("gte", self.gte);
+                table.numbers("in", &self.r#in);
+                table.numbers("not_in", &self.not_in);
+                self.extra_luau(&mut table);
+                table
+            }
+        }
+    };
+}
+
+numeric_rules!(FloatRules, float, f32, {
+    #[prost(bool, optional, tag = "8")]
+    finite: Option<bool>,
+});
+numeric_rules!(DoubleRules, double, f64, {
+    #[prost(bool, optional, tag = "8")]
+    finite: Option<bool>,
+});
+numeric_rules!(Int32Rules, int32, i32);
+numeric_rules!(Int64Rules, int64, i64);
+numeric_rules!(UInt32Rules, uint32, u32);
+numeric_rules!(UInt64Rules, uint64, u64);
+numeric_rules!(SInt32Rules, sint32, i32);
+numeric_rules!(SInt64Rules, sint64, i64);
+numeric_rules!(Fixed32Rules, fixed32, u32);
+numeric_rules!(Fixed64Rules, fixed64, u64);
+numeric_rules!(SFixed32Rules, sfixed32, i32);
+numeric_rules!(SFixed64Rules, sfixed64, i64);
+
+macro_rules! no_extra_luau {
+    ($($name:ident),*) => {
+        $(impl $name {
+            fn extra_luau(&self, _table: &mut LuauTable) {}
+        })*
+    };
+}
+
+no_extra_luau!(
+    Int32Rules,
+    Int64Rules,
+    UInt32Rules,
+    UInt64Rules,
+    SInt32Rules,
+    SInt64Rules,
+    Fixed32Rules,
+    Fixed64Rules,
+    SFixed32Rules,
+    SFixed64Rules
+);
+
+impl FloatRules {
+    fn extra_luau(&self, table: &mut LuauTable) {
+        table.bool("finite", self.finite);
+    }
+}
+
+impl DoubleRules {
+    fn extra_luau(&self, table: &mut LuauTable) {
+        table.bool("finite", self.finite);
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct BoolRules {
+    #[prost(bool, optional, tag = "1")]
+    r#const: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct StringRules {
+    #[prost(string, optional, tag = "1")]
+    r#const: Option<String>,
+    #[prost(uint64, optional, tag = "19")]
+    len: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    min_len: Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    max_len: Option<u64>,
+    #[prost(uint64, optional, tag = "20")]
+    len_bytes: Option<u64>,
+    #[prost(uint64, optional, tag = "4")]
+    min_bytes: Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    max_bytes: Option<u64>,
+    #[prost(string, optional, tag = "6")]
+    pattern: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    prefix: Option<String>,
+    #[prost(string, optional, tag = "8")]
+    suffix: Option<String>,
+    #[prost(string, optional, tag = "9")]
+    contains: Option<String>,
+    #[prost(string, optional, tag = "23")]
+    not_contains: Option<String>,
+    #[prost(string, repeated, tag = "10")]
+    r#in: Vec<String>,
+    #[prost(string, repeated, tag = "11")]
+    not_in: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct BytesRules {
+    #[prost(bytes = "vec", optional, tag = "1")]
+    r#const: Option<Vec<u8>>,
+    #[prost(uint64, optional, tag = "13")]
+    len: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    min_len: Option<u64>,
+    #[prost(uint64, optional, tag = "3")]
+    max_len: Option<u64>,
+    #[prost(string, optional, tag = "4")]
+    pattern: Option<String>,
+    #[prost(bytes = "vec", optional, tag = "5")]
+    prefix: Option<Vec<u8>>,
+    #[prost(bytes = "vec", optional, tag = "6")]
+    suffix: Option<Vec<u8>>,
+    #[prost(bytes = "vec", optional, tag = "7")]
+    contains: Option<Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "8")]
+    r#in: Vec<Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "9")]
+    not_in: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct EnumRules {
+    #[prost(int32, optional, tag = "1")]
+    r#const: Option<i32>,
+    #[prost(bool, optional, tag = "2")]
+    defined_only: Option<bool>,
+    #[prost(int32, repeated, packed = "false", tag = "3")]
+    r#in: Vec<i32>,
+    #[prost(int32, repeated, packed = "false", tag = "4")]
+    not_in: Vec<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct RepeatedRules {
+    #[prost(uint64, optional, tag = "1")]
+    min_items: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    max_items: Option<u64>,
+    #[prost(bool, optional, tag = "3")]
+    unique: Option<bool>,
+    #[prost(message, optional, tag = "4")]
+    items: Option<FieldRules>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct MapRules {
+    #[prost(uint64, optional, tag = "1")]
+    min_pairs: Option<u64>,
+    #[prost(uint64, optional, tag = "2")]
+    max_pairs: Option<u64>,
+    #[prost(message, optional, tag = "4")]
+    keys: Option<FieldRules>,
+    #[prost(message, optional, tag = "5")]
+    values: Option<FieldRules>,
+}
+
+trait LuauNumber: Copy {
+    fn to_luau(self) -> String;
+}
+
+macro_rules! integer_luau_number {
+    ($($rust_type:ty),*) => {
+        $(impl LuauNumber for $rust_type {
+            fn to_luau(self) -> String {
+                self.to_string()
+            }
+        })*
+    };
+}
+
+integer_luau_number!(i32, i64, u32, u64);
+
+impl LuauNumber for f64 {
+    fn to_luau(self) -> String {
+        luau_float_literal(self)
+    }
+}
+
+impl LuauNumber for f32 {
+    fn to_luau(self) -> String {
+        f64::from(self).to_luau()
+    }
+}
+
+/// A Luau table constructor, leaving out everything that isn't set.
+#[derive(Default)]
+struct LuauTable {
+    entries: Vec<(&'static str, String)>,
+}
+
+impl LuauTable {
+    fn entry(&mut self, key: &'static str, value: Option<String>) {
+        if let Some(value) = value {
+            self.entries.push((key, value));
+        }
+    }
+
+    fn number(&mut self, key: &'static str, value: Option<impl LuauNumber>) {
+        self.entry(key, value.map(LuauNumber::to_luau));
+    }
+
+    fn numbers(&mut self, key: &'static str, values: &[impl LuauNumber]) {
+        if !values.is_empty() {
+            let values: Vec<_> = values.iter().map(|value| value.to_luau()).collect();
+            self.entries
+                .push((key, format!("{{ {} }}", values.join(", "))));
+        }
+    }
+
+    fn bool(&mut self, key: &'static str, value: Option<bool>) {
+        self.entry(key, value.map(|value| value.to_string()));
+    }
+
+    fn string(&mut self, key: &'static str, value: Option<&[u8]>) {
+        self.entry(key, value.map(luau_string_literal));
+    }
+
+    fn strings<T: AsRef<[u8]>>(&mut self, key: &'static str, values: &[T]) {
+        if !values.is_empty() {
+            let values: Vec<_> = values
+                .iter()
+                .map(|value| luau_string_literal(value.as_ref()))
+                .collect();
+            self.entries
+                .push((key, format!("{{ {} }}", values.join(", "))));
+        }
+    }
+
+    fn table(&mut self, key: &'static str, table: Option<LuauTable>) {
+        self.entry(key, table.map(|table| table.build()));
+    }
+
+    fn build(&self) -> String {
+        if self.entries.is_empty() {
+            return "{}".to_owned();
+        }
+
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(key, value)| format!("{} = {value}", luau_key(key)))
+            .collect();
+
+        format!("{{ {} }}", entries.join(", "))
+    }
+}
+
+impl BoolRules {
+    fn luau_table(&self) -> LuauTable {
+        let mut table = LuauTable::default();
+        table.bool("const", self.r#const);
+        table
+    }
+}
+
+impl StringRules {
+    fn luau_table(&self) -> LuauTable {
+        let mut table = LuauTable::default();
+        table.string("const", self.r#const.as_deref().map(str::as_bytes));
+        table.number("len", self.len);
+        table.number("min_len", self.min_len);
+        table.number("max_len", self.max_len);
+        table.number("len_bytes", self.len_bytes);
+        table.number("min_bytes", self.min_bytes);
+        table.number("max_bytes", self.max_bytes);
+        table.string("pattern", self.pattern.as_deref().map(str::as_bytes));
+        table.string("prefix", self.prefix.as_deref().map(str::as_bytes));
+        table.string("suffix", self.suffix.as_deref().map(str::as_bytes));
+        table.string("contains", self.contains.as_deref().map(str::as_bytes));
+        table.string(
+            "not_contains",
+            self.not_contains.as_deref().map(str::as_bytes),
+        );
+        table.strings("in", &self.r#in);
+        table.strings("not_in", &self.not_in);
+        table
+    }
+}
+
+impl BytesRules {
+    fn luau_table(&self) -> LuauTable {
+        let mut table = LuauTable::default();
+        table.string("const", self.r#const.as_deref());
+        table.number("len", self.len);
+        table.number("min_len", self.min_len);
+        table.number("max_len", self.max_len);
+        table.string("pattern", self.pattern.as_deref().map(str::as_bytes));
+        table.string("prefix", self.prefix.as_deref());
+        table.string("suffix", self.suffix.as_deref());
+        table.string("contains", self.contains.as_deref());
+        table.strings("in", &self.r#in);
+        table.strings("not_in", &self.not_in);
+        table
+    }
+}
+
+impl EnumRules {
+    fn luau_table(&self) -> LuauTable {
+        let mut table = LuauTable::default();
+        table.number("const", self.r#const);
+        table.bool("defined_only", self.defined_only);
+        table.numbers("in", &self.r#in);
+        table.numbers("not_in", &self.not_in);
+        table
+    }
+}
+
+impl RepeatedRules {
+    fn luau_table(&self) -> LuauTable {
+        let mut table = LuauTable::default();
+        table.number("min_items", self.min_items);
+        table.number("max_items", self.max_items);
+        table.bool("unique", self.unique);
+        table.table("items", self.items.as_ref().map(FieldRules::luau_table));
+        table
+    }
+}
+
+impl MapRules {
+    fn luau_table(&self) -> LuauTable {
+        let mut table = LuauTable::default();
+        table.number("min_pairs", self.min_pairs);
+        table.number("max_pairs", self.max_pairs);
+        table.table("keys", self.keys.as_ref().map(FieldRules::luau_table));
+        table.table("values", self.values.as_ref().map(FieldRules::luau_table));
+        table
+    }
+}
+
+impl FieldRules {
+    fn luau_table(&self) -> LuauTable {
+        let mut table = LuauTable::default();
+        table.bool("required", self.required);
+        table.entry(
+            "ignore",
+            match self.ignore {
+                // IGNORE_IF_UNPOPULATED and IGNORE_IF_DEFAULT_VALUE, from before they were merged.
+                Some(1 | 2) => Some("\"IGNORE_IF_ZERO_VALUE\"".to_owned()),
+                Some(3) => Some("\"IGNORE_ALWAYS\"".to_owned()),
+                _ => None,
+            },
+        );
+
+        table.table("float", self.float.as_ref().map(FloatRules::luau_table));
+        table.table("double", self.double.as_ref().map(DoubleRules::luau_table));
+        table.table("int32", self.int32.as_ref().map(Int32Rules::luau_table));
+        table.table("int64", self.int64.as_ref().map(Int64Rules::luau_table));
+        table.table("uint32", self.uint32.as_ref().map(UInt32Rules::luau_table));
+        table.table("uint64", self.uint64.as_ref().map(UInt64Rules::luau_table));
+        table.table("sint32", self.sint32.as_ref().map(SInt32Rules::luau_table));
+        table.table("sint64", self.sint64.as_ref().map(SInt64Rules::luau_table));
+        table.table(
+            "fixed32",
+            self.fixed32.as_ref().map(Fixed32Rules::luau_table),
+        );
+        table.table(
+            "fixed64",
+            self.fixed64.as_ref().map(Fixed64Rules::luau_table),
+        );
+        table.table(
+            "sfixed32",
+            self.sfixed32.as_ref().map(SFixed32Rules::luau_table),
+        );
+        table.table(
+            "sfixed64",
+            self.sfixed64.as_ref().map(SFixed64Rules::luau_table),
+        );
+        table.table("bool", self.bool.as_ref().map(BoolRules::luau_table));
+        table.table("string", self.string.as_ref().map(StringRules::luau_table));
+        table.table("bytes", self.bytes.as_ref().map(BytesRules::luau_table));
+        table.table("enum", self.r#enum.as_ref().map(EnumRules::luau_table));
+        table.table(
+            "repeated",
+            self.repeated.as_deref().map(RepeatedRules::luau_table),
+        );
+        table.table("map", self.map.as_deref().map(MapRules::luau_table));
+        table
+    }
+}
+
+/// The rules for `proto.validate`, as a Luau table constructor, or `nil` if the message has none.
+pub fn message_validation_rules(
+    options_map: &OptionsMap,
+    message: &DescriptorProto,
+    full_name: &str,
+) -> String {
+    let mut field_entries = Vec::new();
+
+    for field in &message.field {
+        let options = options_map.get(&format!("{full_name}.{}", field.name()));
+        if let Some(rules) = FieldOptions::decode(options)
+            .ok()
+            .and_then(|options| options.field)
+        {
+            field_entries.push(format!(
+                "{} = {}",
+                luau_key(field.name()),
+                rules.luau_table().build()
+            ));
+        }
+    }
+
+    let mut oneof_entries = Vec::new();
+    for oneof in &message.oneof_decl {
+        let options = options_map.get(&format!("{full_name}.{}", oneof.name()));
+        if let Some(true) = OneofOptions::decode(options)
+            .ok()
+            .and_then(|options| options.oneof)
+            .and_then(|rules| rules.required)
+        {
+            oneof_entries.push(format!(
+                "{} = {{ required = true }}",
+                luau_key(oneof.name())
+            ));
+        }
+    }
+
+    if field_entries.is_empty() && oneof_entries.is_empty() {
+        return "nil".to_owned();
+    }
+
+    let mut rules = LuauTable::default();
+    if !field_entries.is_empty() {
+        rules.entry(
+            "fields",
+            Some(format!("{{ {} }}", field_entries.join(", "))),
+        );
+    }
+
+    if !oneof_entries.is_empty() {
+        rules.entry(
+            "oneofs",
+            Some(format!("{{ {} }}", oneof_entries.join(", "))),
+        );
+    }
+
+    rules.build()
+}
+
+/// The buf.validate messages with rules, so that the ones we don't check can be found.
+#[derive(Clone, Copy)]
+enum RulesKind {
+    Field,
+    Number { name: &'static str, float: bool },
+    Bool,
+    String,
+    Bytes,
+    Enum,
+    Repeated,
+    Map,
+    Oneof,
+}
+
+/// Whether a rule is checked by `proto.validate`, with the rules it holds if it's a message of them.
+enum Rule {
+    Checked(Option<RulesKind>),
+    Unchecked(&'static str),
+}
+
+const NUMBER_RULES: [&str; 12] = [
+    "float", "double", "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32",
+    "fixed64", "sfixed32", "sfixed64",
+];
+
+impl RulesKind {
+    /// The prefix of the rules' names, such as `string` in `string.min_len`.
+    fn prefix(self) -> &'static str {
+        match self {
+            RulesKind::Field | RulesKind::Oneof => "",
+            RulesKind::Number { name, .. } => name,
+            RulesKind::Bool => "bool",
+            RulesKind::String => "string",
+            RulesKind::Bytes => "bytes",
+            RulesKind::Enum => "enum",
+            RulesKind::Repeated => "repeated",
+            RulesKind::Map => "map",
+        }
+    }
+
+    fn rule(self, tag: u32) -> Rule {
+        use Rule::{Checked, Unchecked};
+        use RulesKind::*;
+
+        match (self, tag) {
+            (Field, 1..=12) => Checked(Some(Number {
+                name: NUMBER_RULES[tag as usize - 1],
+                float: tag <= 2,
+            })),
+            (Field, 13) => Checked(Some(Bool)),
+            (Field, 14) => Checked(Some(String)),
+            (Field, 15) => Checked(Some(Bytes)),
+            (Field, 16) => Checked(Some(Enum)),
+            (Field, 18) => Checked(Some(Repeated)),
+            (Field, 19) => Checked(Some(Map)),
+            (Field, 25 | 27) => Checked(None),
+            (Field, 20) => Unchecked("any"),
+            (Field, 21) => Unchecked("duration"),
+            (Field, 22) => Unchecked("timestamp"),
+            (Field, 23) => Unchecked("cel"),
+
+            // `example` is only documentation, at 8 for integers, and 9 for floats after `finite`.
+            (Number { float: false, .. }, 1..=8) | (Number { float: true, .. }, 1..=9) => {
+                Checked(None)
+            }
+            (Bool, 1 | 2) => Checked(None),
+            (String, 1..=11 | 19 | 20 | 23 | 34) => Checked(None),
+            (String, 12) => Unchecked("email"),
+            (String, 13) => Unchecked("hostname"),
+            (String, 14) => Unchecked("ip"),
+            (String, 15) => Unchecked("ipv4"),
+            (String, 16) => Unchecked("ipv6"),
+            (String, 17) => Unchecked("uri"),
+            (String, 18) => Unchecked("uri_ref"),
+            (String, 21) => Unchecked("address"),
+            (String, 22) => Unchecked("uuid"),
+            (String, 24) => Unchecked("well_known_regex"),
+            (String, 25) => Unchecked("strict"),
+            (Bytes, 1..=9 | 13 | 14) => Checked(None),
+            (Bytes, 10) => Unchecked("ip"),
+            (Bytes, 11) => Unchecked("ipv4"),
+            (Bytes, 12) => Unchecked("ipv6"),
+            (Enum, 1..=5) => Checked(None),
+            (Repeated, 1..=3) => Checked(None),
+            (Repeated, 4) => Checked(Some(Field)),
+            (Map, 1 | 2) => Checked(None),
+            (Map, 4 | 5) => Checked(Some(Field)),
+            (Oneof, 1) => Checked(None),
+            _ => Unchecked(""),
+        }
+    }
+
+    /// Adds an error for each rule in `buffer` that isn't checked to `unchecked`.
+    fn find_unchecked(self, mut buffer: &[u8], unchecked: &mut Vec<String>) {
+        while buffer.has_remaining() {
+            let Ok((tag, wire_type)) = decode_key(&mut buffer) else {
+                unchecked.push("buf.validate rules can't be decoded".to_owned());
+                return;
+            };
+
+            // Patterns are checked, but only in the syntax proto/regex.luau supports.
+            if matches!((self, tag), (RulesKind::String, 6) | (RulesKind::Bytes, 4))
+                && wire_type == WireType::LengthDelimited
+            {
+                let Some(pattern) = length_delimited(&mut buffer) else {
+                    unchecked.push("buf.validate rules can't be decoded".to_owned());
+                    return;
+                };
+
+                let pattern = String::from_utf8_lossy(pattern);
+                if let Some(reason) = regex::unsupported_reason(&pattern) {
+                    unchecked.push(format!(
+                        "buf.validate rule {}.pattern `{pattern}` is not supported: {reason}",
+                        self.prefix()
+                    ));
+                }
+
+                continue;
+            }
+
+            let name = match self.rule(tag) {
+                Rule::Checked(Some(kind)) if wire_type == WireType::LengthDelimited => {
+                    let Some(rules) = length_delimited(&mut buffer) else {
+                        unchecked.push("buf.validate rules can't be decoded".to_owned());
+                        return;
+                    };
+
+                    kind.find_unchecked(rules, unchecked);
+                    continue;
+                }
+
+                Rule::Checked(_) => None,
+                Rule::Unchecked("") => Some(format!("field {tag}")),
+                Rule::Unchecked(name) => Some(name.to_owned()),
+            };
+
+            if let Some(name) = name {
+                let rule = match self.prefix() {
+                    "" => name,
+                    prefix => format!("{prefix}.{name}"),
+                };
+
+                unchecked.push(format!("buf.validate rule {rule} is not supported"));
+            }
+
+            if skip_field(wire_type, tag, &mut buffer, DecodeContext::default()).is_err() {
+                unchecked.push("buf.validate rules can't be decoded".to_owned());
+                return;
+            }
+        }
+    }
+}
+
+fn length_delimited<'a>(buffer: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let length = usize::try_from(decode_varint(buffer).ok()?).ok()?;
+    let value = buffer.get(..length)?;
+    buffer.advance(length);
+    Some(value)
+}
+
+/// The `(buf.validate.*)` option in serialized options, all of which use the same field number.
+fn rules_option(mut options: &[u8]) -> Option<&[u8]> {
+    while let Ok((tag, wire_type)) = decode_key(&mut options) {
+        if tag == 1159 && wire_type == WireType::LengthDelimited {
+            return length_delimited(&mut options);
+        }
+
+        skip_field(wire_type, tag, &mut options, DecodeContext::default()).ok()?;
+    }
+
+    None
+}
+
+/// The buf.validate rules on a message and its fields and oneofs that `proto.validate` doesn't
+/// check, such as CEL expressions and `string.email`, as errors. Skipping them quietly would let
+/// through messages that other protovalidate libraries reject.
+pub fn unsupported_validation_rules(
+    options_map: &OptionsMap,
+    message: &DescriptorProto,
+    full_name: &str,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    // `(buf.validate.message)` only has CEL expressions and oneof rules across several fields.
+    if rules_option(options_map.get(full_name)).is_some() {
+        errors.push(format!(
+            "{full_name}: buf.validate message rules are not supported"
+        ));
+    }
+
+    let mut report = |name: String, kind: RulesKind| {
+        let Some(rules) = rules_option(options_map.get(&name)) else {
+            return;
+        };
+
+        let mut unchecked = Vec::new();
+        kind.find_unchecked(rules, &mut unchecked);
+
+        for problem in unchecked {
+            errors.push(format!("{name}: {problem}"));
+        }
+    };
+
+    for field in &message.field {
+        report(format!("{full_name}.{}", field.name()), RulesKind::Field);
+    }
+
+    for oneof in &message.oneof_decl {
+        report(format!("{full_name}.{}", oneof.name()), RulesKind::Oneof);
+    }
+
+    errors
+}
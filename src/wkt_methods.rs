@@ -0,0 +1,155 @@
+use prost_types::{DescriptorProto, FileDescriptorProto};
+
+pub struct WktMethods {
+    pub signatures: &'static str,
+    pub code: &'static str,
+}
+
+impl WktMethods {
+    pub fn try_create(
+        file_descriptor_proto: &FileDescriptorProto,
+        message: &DescriptorProto,
+    ) -> Option<Self> {
+        if file_descriptor_proto.package() != "google.protobuf" {
+            return None;
+        }
+
+        match message.name() {
+            "Any" => Some(WktMethods {
+                signatures: ANY_METHOD_SIGNATURES,
+                code: include_str!("./luau/wkt_mixins/Any_methods.luau"),
+            }),
+
+            "Duration" => Some(WktMethods {
+                signatures: DURATION_METHOD_SIGNATURES,
+                code: include_str!("./luau/wkt_mixins/Duration_methods.luau"),
+            }),
+
+            "ListValue" => Some(WktMethods {
+                signatures: LIST_VALUE_METHOD_SIGNATURES,
+                code: include_str!("./luau/wkt_mixins/ListValue_methods.luau"),
+            }),
+
+            "Struct" => Some(WktMethods {
+                signatures: STRUCT_METHOD_SIGNATURES,
+                code: include_str!("./luau/wkt_mixins/Struct_methods.luau"),
+            }),
+
+            "Timestamp" => Some(WktMethods {
+                signatures: TIMESTAMP_METHOD_SIGNATURES,
+                code: include_str!("./luau/wkt_mixins/Timestamp_methods.luau"),
+            }),
+
+            "Value" => Some(WktMethods {
+                signatures: VALUE_METHOD_SIGNATURES,
+                code: include_str!("./luau/wkt_mixins/Value_methods.luau"),
+            }),
+
+            _ => None,
+        }
+    }
+}
+
+const ANY_METHOD_SIGNATURES: &str = r#"
+-- The type URL prefix used by pack when none is given. Defaults to "type.googleapis.com",
+-- the same as other Protobuf libraries.
+defaultTypeUrlPrefix: string,
+
+-- Pack a message into an Any.
+--
+-- typeUrlPrefix should be the base URL for the type URL. For example, Google uses
+-- "type.googleapis.com". Defaults to Any.defaultTypeUrlPrefix.
+pack: (payload: proto.Message<any, any>, typeUrlPrefix: string?) -> Any,
+
+-- Returns the message contained by the Any, decoded as messageType (or nil if the Any is empty).
+-- Errors if the Any holds a different type.
+-- Without a messageType, the type is looked up in typeRegistry.default.
+unpack: <T>(self: Any, messageType: { decode: (buffer) -> T, descriptor: proto.Descriptor }?) -> T?,
+
+-- Returns the message contained by the Any (or nil if the Any is empty), looking up its type
+-- in the registry. Errors if the type can't be found.
+unpackWith: (self: Any, registry: typeRegistry.TypeRegistry) -> proto.Message<any, any>?,
+
+-- Returns true if and only if the Any contains a message of the given type.
+is: (self: Any, messageType: { descriptor: proto.Descriptor }) -> boolean,
+
+-- Returns true if and only if the Any contains an object of the type specified by
+-- typeName. If typeName is a full type URL, it will be compared; otherwise,
+-- only the type name will be compared.
+isA: (self: Any, typeName: string) -> boolean,
+
+-- Returns the full name of the type in a type URL.
+typeUrlToTypeName: (typeUrl: string) -> string,
+"#;
+
+const DURATION_METHOD_SIGNATURES: &str = r#"
+-- Creates a Duration from a number of seconds, which can be fractional or negative.
+fromSeconds: (seconds: number) -> Duration,
+
+-- Creates a Duration from a number of milliseconds, which can be fractional or negative.
+fromMillis: (millis: number) -> Duration,
+
+-- Returns the duration in seconds, including the fraction from nanos.
+toSeconds: (self: Duration) -> number,
+
+-- Returns the duration in whole milliseconds, rounded towards zero.
+toMillis: (self: Duration) -> number,
+
+-- Carries nanos over 1 second into seconds, and gives seconds and nanos the same sign,
+-- as the Duration spec requires. Modifies the Duration in place and returns it.
+normalize: (self: Duration) -> Duration,
+"#;
+
+const LIST_VALUE_METHOD_SIGNATURES: &str = r#"
+-- Creates a ListValue from an array, converting each element with Value.from.
+fromArray: (input: { any }) -> ListValue,
+
+-- Returns the values as an array, unwrapping each one with Value.unwrap.
+-- Null values become nil, leaving holes in the array.
+toArray: (self: ListValue) -> { any },
+"#;
+
+const STRUCT_METHOD_SIGNATURES: &str = r#"
+-- Creates a Struct from a table, converting each value with Value.from.
+-- Errors if a key isn't a string.
+fromTable: (input: { [string]: any }) -> Struct,
+
+-- Returns the fields as a table, unwrapping each value with Value.unwrap.
+-- Fields that are null are left out, since a table can't hold nil.
+toTable: (self: Struct) -> { [string]: any },
+"#;
+
+const TIMESTAMP_METHOD_SIGNATURES: &str = r#"
+-- Creates a Timestamp from seconds since the Unix epoch, which can be fractional.
+fromUnixSeconds: (seconds: number) -> Timestamp,
+
+-- Creates a Timestamp from milliseconds since the Unix epoch.
+fromMillis: (millis: number) -> Timestamp,
+
+-- Creates a Timestamp from a Roblox DateTime.
+fromDateTime: (dateTime: DateTime) -> Timestamp,
+
+-- Returns the seconds since the Unix epoch, including the fraction from nanos.
+toUnixSeconds: (self: Timestamp) -> number,
+
+-- Returns the whole milliseconds since the Unix epoch, rounded down.
+toMillis: (self: Timestamp) -> number,
+
+-- Returns a Roblox DateTime. DateTime only has millisecond precision.
+toDateTime: (self: Timestamp) -> DateTime,
+
+-- Carries nanos outside of [0, 1 second) into seconds, as the Timestamp spec requires.
+-- Modifies the Timestamp in place and returns it.
+normalize: (self: Timestamp) -> Timestamp,
+"#;
+
+const VALUE_METHOD_SIGNATURES: &str = r#"
+-- Creates a Value from a Luau value. nil becomes null, tables with only the keys 1 to n
+-- (including empty tables) become lists, and other tables become structs.
+-- Errors on NaN and infinities, which can't be represented in JSON, and on types
+-- such as functions and buffers.
+from: (input: any) -> Value,
+
+-- Returns the Luau value held by the Value, the reverse of Value.from. Null becomes nil.
+unwrap: (self: Value) -> any,
+"#;